@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::error;
 use crate::input::{ 
     InputMode, 
@@ -5,31 +6,67 @@ use crate::input::{
     InputContent,
     ListType,
     TransRecord,
+    SplitRecord,
     Account,
     TransList,
+    TransSortColumn,
     AccountList,
-    TODO_HEADER_STYLE,
-    NORMAL_ROW_BG,
-    ALT_ROW_BG_COLOR,
-    SELECTED_STYLE,
+    Budget,
+    BudgetList,
+    RecurringRecord,
+    RecurringList,
+    NetWorth,
+    SpendingCharts,
+    Dashboard,
+    Confirmation,
+    ConfirmAction,
+    ClickTarget,
+    StatusMessage,
+    UndoAction,
+    parse_date_input,
+    UNDO_LIMIT,
+    CategoryUsage,
+    suggest_categories,
+    HISTORY_GRANULARITIES,
+    CHART_RANGES,
+    TRANS_SORT_COLUMNS,
+    RECURRING_FREQUENCIES,
  };
 use crate::client::{
+    set_actor,
     query_or_create_user,
+    query_user_summary,
+    query_dashboard,
+    query_category_usage,
+    query_net_worth_history,
+    query_spending_charts,
     create_or_update_account,
     query_account,
     create_or_update_transaction,
+    upload_attachment,
     delete_account,
     delete_transaction,
-    delete_user
+    delete_user,
+    restore_from_trash,
+    query_budgets,
+    create_or_update_budget,
+    delete_budget,
+    query_recurring,
+    create_or_update_recurring,
+    pause_recurring,
+    delete_recurring,
 };
-use chrono::Local;
+use crate::command::{complete, run_command, COMMAND_HISTORY_LIMIT};
+use crate::keymap::{Action, Keymap};
+use crate::theme::Theme;
+use chrono::{Local, Months};
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
-    style::{Color, Stylize},
+    layout::{Constraint, Position, Rect},
+    style::{Color, Style, Stylize},
     symbols,
-    text::Line,
-    widgets::{Block, Borders, HighlightSpacing, List, ListItem, StatefulWidget},
+    text::{Line, Span},
+    widgets::{Block, Borders, HighlightSpacing, LineGauge, List, ListItem, Paragraph, Row, StatefulWidget, Table, Widget, Wrap},
 };
 
 /// Application result type.
@@ -46,15 +83,36 @@ pub struct App {
     pub username: String,
     /// List of user accounts
     pub accounts: AccountList,
+    /// Assets, liabilities and net worth across all user accounts
+    pub net_worth: NetWorth,
+    /// Net worth at the end of each period, oldest first
+    pub net_worth_history: Vec<(String, f64)>,
+    /// Period length of the net worth history (Daily/Weekly/Monthly)
+    pub history_granularity: String,
+    /// Spending, income vs. expenses and balance of the selected account
+    pub spending_charts: SpendingCharts,
+    /// Time range of the charts, one of CHART_RANGES
+    pub chart_range: String,
+    /// This month's flow, top categories, upcoming schedules and credit use
+    pub dashboard: Dashboard,
     /// Selected account_id
     // pub account_selected_idx: usize,
     /// List of transaction history
     pub trans_history: TransList,
 
 
-    /// new or selected account/transaction
+    /// Monthly budgets with spending for the current month
+    pub budgets: BudgetList,
+    /// Warnings for budgets overspent by the last saved transaction
+    pub budget_alerts: Vec<String>,
+    /// Recurring transaction schedules of the user
+    pub recurring: RecurringList,
+
+    /// new or selected account/transaction/budget
     pub new_account: Account,
     pub new_trans: TransRecord,
+    pub new_budget: Budget,
+    pub new_recurring: RecurringRecord,
 
 
     /// Current input mode
@@ -71,11 +129,48 @@ pub struct App {
 
     pub new_trans_question_list: Vec<InputContent>,
     pub new_acct_question_list: Vec<InputContent>,
-    pub debug_msg: String,
+    pub new_budget_question_list: Vec<InputContent>,
+    pub new_recurring_question_list: Vec<InputContent>,
+    pub status: StatusMessage,
 
     pub acct_balance: String,
     pub filter_trans_type: String,
     pub filter_trans_category: String,
+    pub filter_trans_tags: String,
+    pub filter_tag_match: String,
+    pub attachment_path: String,
+    // changes that can be undone, most recent last, and the undone ones that
+    // can be replayed
+    pub undo_stack: Vec<UndoAction>,
+    pub redo_stack: Vec<UndoAction>,
+    // message shown under each field whose value is not acceptable
+    pub input_errors: HashMap<InputContent, String>,
+    // destructive action shown in a popup until it is confirmed or cancelled
+    pub confirmation: Option<Confirmation>,
+    // screen regions of the last frame that react to the mouse, topmost last
+    pub hit_regions: Vec<(Rect, ClickTarget)>,
+    // keys bound to each action, from the keymap file or the defaults
+    pub keymap: Keymap,
+    // colors in use, and the themes that can be switched to
+    pub theme: Theme,
+    pub themes: Vec<Theme>,
+    // text typed after ':' and the commands run before it, most recent last,
+    // with the one being recalled with the arrows
+    pub command_input: String,
+    pub command_history: Vec<String>,
+    pub command_history_idx: Option<usize>,
+    // text the transaction list is searched for, and the transactions loaded
+    // before searching
+    pub search_query: String,
+    pub unsearched_trans: Vec<TransRecord>,
+    // the user's categories suggested while typing one, the suggestion picked
+    // with the arrows, and the field being typed into when it suggests
+    pub categories: Vec<CategoryUsage>,
+    pub suggestion_idx: usize,
+    pub suggestion_area: Option<Rect>,
+    /// categories are loaded when typing into a category field starts, and
+    /// again only after a change may have added some
+    pub categories_stale: bool,
 }
 
 impl Default for App {
@@ -85,7 +180,16 @@ impl Default for App {
             counter: 0,
             username: String::new(), // Default to an empty string
             accounts: AccountList::from_iter([]),   
+            net_worth: NetWorth::default(),
+            net_worth_history: Vec::new(),
+            history_granularity: HISTORY_GRANULARITIES[2].to_string(),
+            spending_charts: SpendingCharts::default(),
+            chart_range: CHART_RANGES[0].to_string(),
+            dashboard: Dashboard::default(),
             trans_history: TransList::from_iter([]),  
+            budgets: BudgetList::from_iter([]),
+            budget_alerts: Vec::new(),
+            recurring: RecurringList::from_iter([]),
             new_account: Account::new(
                 "",
                 "",
@@ -101,6 +205,8 @@ impl Default for App {
                 "",
                 0.0,
             ),
+            new_budget: Budget::new("", "", "", 0.0),
+            new_recurring: RecurringRecord::default(),
             input_mode: InputMode::Normal,   // Default to not inputting
             input: String::new(), // Default to an empty string
             character_index: 0,
@@ -109,9 +215,13 @@ impl Default for App {
             list_content: ListType::Acct,
             new_trans_question_list: vec![
                 InputContent::TransactionDescription,
+                InputContent::TransactionDate,
                 InputContent::TransactionType,
                 InputContent::TransactionAmount,
                 InputContent::TransactionCategory,
+                InputContent::TransactionTags,
+                InputContent::TransactionSplits,
+                InputContent::TransactionAttachment,
             ],
             new_acct_question_list: vec![
                 InputContent::AccountName,
@@ -119,18 +229,66 @@ impl Default for App {
                 InputContent::AccountLimit,
                 InputContent::FilterTransType,
                 InputContent::FilterTransCategory,
+                InputContent::FilterTransTags,
+                InputContent::FilterTagMatch,
+            ],
+            new_budget_question_list: vec![
+                InputContent::BudgetCategory,
+                InputContent::BudgetAmount,
+                InputContent::BudgetAccount,
+            ],
+            new_recurring_question_list: vec![
+                InputContent::RecurringDescription,
+                InputContent::RecurringType,
+                InputContent::RecurringAmount,
+                InputContent::RecurringCategory,
+                InputContent::RecurringFrequency,
+                InputContent::RecurringStartDate,
+                InputContent::RecurringEndDate,
             ],
-            debug_msg: String::new(),
+            status: StatusMessage::default(),
             acct_balance: String::new(),
             filter_trans_type: String::new(),
             filter_trans_category: String::new(),
+            filter_trans_tags: String::new(),
+            filter_tag_match: String::new(),
+            attachment_path: String::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            input_errors: HashMap::new(),
+            confirmation: None,
+            hit_regions: Vec::new(),
+            keymap: Keymap::default(),
+            theme: Theme::default(),
+            themes: Theme::built_in(),
+            command_input: String::new(),
+            command_history: Vec::new(),
+            command_history_idx: None,
+            search_query: String::new(),
+            unsearched_trans: Vec::new(),
+            categories: Vec::new(),
+            categories_stale: true,
+            suggestion_idx: 0,
+            suggestion_area: None,
         }
     }
 }
 
 impl App {
     pub fn new() -> Self {
-        Self::default()
+        let mut app = Self::default();
+        let (keymap, error) = Keymap::load();
+        app.keymap = keymap;
+        if let Some(e) = error {
+            app.set_status(e, true);
+        }
+        let (themes, selected, error) = Theme::load();
+        app.theme = themes[selected].clone();
+        app.themes = themes;
+        if let Some(e) = error {
+            app.set_status(e, true);
+        }
+        app
     }
     
     pub fn quit(&mut self) {
@@ -164,9 +322,11 @@ impl App {
         let index = self.byte_index();
         self.input.insert(index, new_char);
         self.move_cursor_right();
+        self.suggestion_idx = 0;
     }
 
     pub fn delete_char(&mut self) {
+        self.suggestion_idx = 0;
         let is_not_cursor_leftmost = self.character_index != 0;
         if is_not_cursor_leftmost {
             // Method "remove" is not used on the saved text for deleting the selected char.
@@ -188,10 +348,140 @@ impl App {
         }
     }
 
+    fn set_status(&mut self, text: String, is_error: bool) {
+        self.status = StatusMessage {
+            time: Local::now().format("%H:%M:%S").to_string(),
+            text,
+            is_error,
+        };
+    }
+
+    // Shows the outcome of a change in the status bar
+    fn report<T>(&mut self, success: &str, result: Result<T, String>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.set_status(success.to_string(), false);
+                Some(value)
+            },
+            Err(e) => {
+                self.set_status(e, true);
+                None
+            }
+        }
+    }
+
+    // Shows a failed query in the status bar, leaving the outcome of the last
+    // change on display when it succeeds
+    fn report_error<T>(&mut self, result: Result<T, String>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.set_status(e, true);
+                None
+            }
+        }
+    }
+
+    // Opens the confirmation popup describing what the action would delete,
+    // unless there is nothing selected to delete
+    pub fn request_confirmation(&mut self, action: ConfirmAction) {
+        let confirmation = match action {
+            ConfirmAction::DeleteUser => {
+                let transactions: i64 = self.accounts.items.iter().map(|a| a.transaction_count).sum();
+                Confirmation {
+                    title: format!("Move user {} to the trash?", self.username),
+                    details: vec![
+                        format!("{} accounts with {} transactions go along with it", self.accounts.items.len(), transactions),
+                        format!("Type the username {} to confirm", self.username),
+                    ],
+                    typed_text: Some(self.username.clone()),
+                    action,
+                }
+            },
+            ConfirmAction::DeleteAccount => {
+                let Some(account) = self.find_account(&self.new_account.acct_id) else {
+                    return;
+                };
+                Confirmation {
+                    title: format!("Move account {} to the trash?", account.acct_name),
+                    details: vec![
+                        format!("{} account with {} transactions", account.acct_type, account.transaction_count),
+                        format!("Balance: {:.2}", account.balance),
+                    ],
+                    typed_text: None,
+                    action,
+                }
+            },
+            ConfirmAction::DeleteTransaction => {
+                if self.new_trans.transaction_id.is_empty() {
+                    return;
+                }
+                let mut details = vec![format!(
+                    "{} {} of {} in {}",
+                    self.new_trans.timestamp, self.new_trans.trans_type, self.new_trans.amount, self.new_trans.category,
+                )];
+                if self.new_trans.attachment_count > 0 {
+                    details.push(format!("{} attached files", self.new_trans.attachment_count));
+                }
+                Confirmation {
+                    title: format!("Move transaction {} to the trash?", self.new_trans.description),
+                    details,
+                    typed_text: None,
+                    action,
+                }
+            },
+            ConfirmAction::DeleteBudget => {
+                if self.new_budget.budget_id.is_empty() {
+                    return;
+                }
+                Confirmation {
+                    title: format!("Delete the budget for {}?", self.new_budget.category),
+                    details: vec![
+                        format!("Budgeted {:.2} a month", self.new_budget.budgeted),
+                        String::from("This cannot be undone"),
+                    ],
+                    typed_text: None,
+                    action,
+                }
+            },
+            ConfirmAction::DeleteRecurring => {
+                if self.new_recurring.recurring_id.is_empty() {
+                    return;
+                }
+                Confirmation {
+                    title: format!("Delete the schedule {}?", self.new_recurring.description),
+                    details: vec![
+                        format!("{} {} of {}", self.new_recurring.frequency, self.new_recurring.trans_type, self.new_recurring.amount),
+                        String::from("This cannot be undone"),
+                    ],
+                    typed_text: None,
+                    action,
+                }
+            },
+        };
+        self.input.clear();
+        self.character_index = 0;
+        self.confirmation = Some(confirmation);
+    }
+
+    pub fn cancel_confirmation(&mut self) {
+        self.confirmation = None;
+        self.input.clear();
+        self.character_index = 0;
+    }
+
+    // Whether the text typed into the popup, if any is required, matches
+    pub fn confirmation_ready(&self) -> bool {
+        match &self.confirmation {
+            Some(confirmation) => confirmation.typed_text.as_ref().is_none_or(|text| *text == self.input),
+            None => false,
+        }
+    }
+
     pub async fn refresh_user_data(&mut self) {
-        let accounts =
-            if let Ok(accts) = query_or_create_user(&self.username).await {
-                accts
+        let (accounts, net_worth) =
+            if let Some(summary) = self.report_error(query_user_summary(&self.username).await) {
+                summary
             } else {
                 return;
             };
@@ -199,97 +489,482 @@ impl App {
         // clear the current account list
         self.accounts.items.clear();
 
-        // populate loaded accounts
-        for account in accounts.iter() {
-            self.accounts.items.push(Account::new(
-                &account.acct_id,
-                &account.acct_name,
-                &account.user_id,
-                &account.acct_type,
-                account.card_limit,
-            ));
+        // populate loaded accounts along with their balances
+        self.accounts.items = accounts;
+        self.net_worth = net_worth;
+        // transactions may have brought in new categories
+        self.categories_stale = true;
+    }
+
+    // Starts typing into the focused field. Selectors start from the field's
+    // current value, category fields load the categories to suggest.
+    pub async fn start_editing(&mut self) {
+        if self.input_content.is_category() && self.categories_stale {
+            self.refresh_categories().await;
         }
+        if let Some(choices) = self.input_content.choices() {
+            let current = self.field_value(&self.input_content);
+            let idx = choices.iter().position(|c| c.eq_ignore_ascii_case(&current)).unwrap_or(0);
+            self.set_choice(choices[idx]);
+        }
+        self.input_mode = InputMode::Editing;
     }
 
-    pub async fn delete_user(&mut self) {
-        self.debug_msg = format!("{:?} deleting", self.username);
+    fn set_choice(&mut self, choice: &str) {
+        self.input = choice.to_string();
+        self.character_index = self.input.chars().count();
+    }
 
-        if self.username != "" {
-            let deletion_status =
-                if let Ok(ds) = delete_user(&self.username).await {
-                    ds
-                } else {
-                    return;
-                };
-            self.debug_msg = format!("{:?} deleted", deletion_status);
-            self.username = "".to_string();        
+    // Moves the selector being edited to the next or previous value,
+    // wrapping around
+    pub fn cycle_choice(&mut self, next: bool) {
+        let Some(choices) = self.input_content.choices() else {
+            return;
+        };
+        let count = choices.len();
+        let idx = choices.iter().position(|c| *c == self.input).unwrap_or(0);
+        let idx = if next { (idx + 1) % count } else { (idx + count - 1) % count };
+        self.set_choice(choices[idx]);
+    }
+
+    // Moves the selector to the next value starting with the letter
+    pub fn jump_to_choice(&mut self, letter: char) {
+        let Some(choices) = self.input_content.choices() else {
+            return;
+        };
+        let letter = letter.to_ascii_lowercase();
+        let idx = choices.iter().position(|c| *c == self.input).unwrap_or(0);
+        let found = (1..=choices.len())
+            .map(|step| choices[(idx + step) % choices.len()])
+            .find(|c| c.to_lowercase().starts_with(letter));
+        if let Some(choice) = found {
+            self.set_choice(choice);
+        }
+    }
+
+    pub fn pick_choice(&mut self, idx: usize) {
+        if let Some(choice) = self.input_content.choices().and_then(|choices| choices.get(idx).copied()) {
+            self.set_choice(choice);
+        }
+    }
+
+    pub async fn refresh_categories(&mut self) {
+        if let Some(categories) = self.report_error(query_category_usage(&self.username).await) {
+            self.categories = categories;
+            self.categories_stale = false;
+        }
+    }
+
+    // Categories to suggest for the field being typed into, if it takes one
+    pub fn category_suggestions(&self) -> Vec<&CategoryUsage> {
+        if self.input_mode != InputMode::Editing || !self.input_content.is_category() {
+            return Vec::new();
+        }
+        suggest_categories(&self.categories, &self.input)
+    }
+
+    pub fn select_suggestion(&mut self, next: bool) {
+        let count = self.category_suggestions().len();
+        if count == 0 {
+            return;
+        }
+        self.suggestion_idx = if next {
+            (self.suggestion_idx + 1) % count
+        } else {
+            (self.suggestion_idx + count - 1) % count
+        };
+    }
+
+    // Replaces what has been typed with the picked suggestion
+    pub fn accept_suggestion(&mut self) {
+        let Some(name) = self.category_suggestions().get(self.suggestion_idx).map(|c| c.name.clone()) else {
+            return;
+        };
+        self.character_index = name.chars().count();
+        self.input = name;
+        self.suggestion_idx = 0;
+    }
+
+    // Loads the accounts along with the dashboard, so the home page needs a
+    // single call
+    pub async fn refresh_dashboard(&mut self) {
+        let (accounts, net_worth, dashboard) =
+            if let Some(d) = self.report_error(query_dashboard(&self.username).await) {
+                d
+            } else {
+                return;
+            };
+        self.accounts.items = accounts;
+        self.net_worth = net_worth;
+        self.dashboard = dashboard;
+        self.categories_stale = true;
+    }
+
+    // Jumps from the dashboard to the account page of the idx-th account
+    pub async fn open_account(&mut self, idx: usize) {
+        let Some(account) = self.accounts.items.get(idx) else {
+            return;
+        };
+        self.new_account = account.clone();
+        self.accounts.state.select(None);
+        self.filter_trans_category = "".to_string();
+        self.filter_trans_type = "".to_string();
+        self.filter_trans_tags = "".to_string();
+        self.filter_tag_match = "".to_string();
+        self.search_query.clear();
+        self.input_errors.clear();
+        self.input_content = InputContent::AccountName;
+        self.input_mode = InputMode::Normal;
+        self.page = Page::AccountDetails;
+        self.refresh_transactions().await;
+    }
+
+    pub async fn refresh_net_worth_history(&mut self) {
+        let history =
+            if let Some(h) = self.report_error(query_net_worth_history(&self.username, &self.history_granularity).await) {
+                h
+            } else {
+                return;
+            };
+        self.net_worth_history = history;
+    }
+
+    pub async fn cycle_history_granularity(&mut self) {
+        let idx = HISTORY_GRANULARITIES
+            .iter()
+            .position(|g| *g == self.history_granularity)
+            .unwrap_or(0);
+        self.history_granularity = HISTORY_GRANULARITIES[(idx + 1) % HISTORY_GRANULARITIES.len()].to_string();
+        self.refresh_net_worth_history().await;
+    }
+
+    // Charts follow the transaction filters of the account page
+    pub async fn refresh_spending_charts(&mut self) {
+        if self.new_account.acct_id.is_empty() {
+            return;
+        }
+        let months = match self.chart_range.as_str() {
+            "3 Months" => Some(3),
+            "6 Months" => Some(6),
+            "12 Months" => Some(12),
+            _ => None,
+        };
+        let start_date = months
+            .and_then(|m| Local::now().date_naive().checked_sub_months(Months::new(m)))
+            .map(|date| date.to_string());
+        let charts =
+            if let Some(c) = self.report_error(query_spending_charts(
+                self.new_account.acct_id.parse().unwrap_or(0),
+                if self.filter_trans_type.is_empty() { None } else { Some(self.filter_trans_type.clone()) },
+                if self.filter_trans_category.is_empty() { None } else { Some(self.filter_trans_category.clone()) },
+                if self.filter_trans_tags.is_empty() { None } else { Some(self.filter_trans_tags.clone()) },
+                if self.filter_tag_match.is_empty() { None } else { Some(self.filter_tag_match.clone()) },
+                start_date,
+            ).await) {
+                c
+            } else {
+                return;
+            };
+        self.spending_charts = charts;
+    }
+
+    pub async fn cycle_chart_range(&mut self) {
+        let idx = CHART_RANGES
+            .iter()
+            .position(|r| *r == self.chart_range)
+            .unwrap_or(0);
+        self.chart_range = CHART_RANGES[(idx + 1) % CHART_RANGES.len()].to_string();
+        self.refresh_spending_charts().await;
+    }
+
+    pub fn cycle_theme(&mut self) {
+        let idx = self.themes
+            .iter()
+            .position(|t| t.name == self.theme.name)
+            .unwrap_or(0);
+        self.theme = self.themes[(idx + 1) % self.themes.len()].clone();
+        self.set_status(format!("Switched to the {} theme", self.theme.name), false);
+    }
+
+    pub fn open_command_palette(&mut self) {
+        self.command_input.clear();
+        self.command_history_idx = None;
+        self.input_mode = InputMode::Command;
+    }
+
+    pub fn close_command_palette(&mut self) {
+        self.command_input.clear();
+        self.input_mode = InputMode::Normal;
+    }
+
+    // Steps through the command history, back to the empty prompt past the
+    // most recent command
+    pub fn recall_command(&mut self, older: bool) {
+        let last = self.command_history.len();
+        let idx = match (self.command_history_idx, older) {
+            (None, true) if last > 0 => Some(last - 1),
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < last => Some(i + 1),
+            _ => None,
+        };
+        self.command_history_idx = idx;
+        self.command_input = idx.map(|i| self.command_history[i].clone()).unwrap_or_default();
+    }
+
+    pub fn complete_command(&mut self) {
+        if let Some(completed) = complete(&self.command_input) {
+            self.command_input = completed;
+        }
+    }
+
+    pub async fn submit_command(&mut self) {
+        let line = self.command_input.trim().to_string();
+        self.close_command_palette();
+        if line.is_empty() {
+            return;
+        }
+        self.command_history.retain(|c| *c != line);
+        self.command_history.push(line.clone());
+        if self.command_history.len() > COMMAND_HISTORY_LIMIT {
+            self.command_history.remove(0);
+        }
+        let result = run_command(self, &line).await;
+        self.report_error(result);
+    }
+
+    // Writes the listed transactions, filters and sort order included, to a
+    // CSV file named after the account unless a path is given
+    pub fn export_transactions(&mut self, path: &str) -> Result<(), String> {
+        let path = if path.is_empty() {
+            format!(
+                "{}-{}.csv",
+                self.new_account.acct_name.to_lowercase().replace(' ', "-"),
+                Local::now().date_naive(),
+            )
         } else {
-            self.debug_msg = format!("deletion not triggered");
+            path.to_string()
+        };
+        let quote = |field: &str| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        };
+        let mut csv = String::from("date,type,category,memo,amount,balance,tags\n");
+        for trans in self.trans_history.items.iter() {
+            csv.push_str(&format!(
+                "{},{},{},{},{:.2},{:.2},{}\n",
+                quote(&trans.timestamp),
+                quote(&trans.trans_type),
+                quote(&trans.category),
+                quote(&trans.description),
+                trans.amount,
+                trans.balance,
+                quote(&trans.tags.join(",")),
+            ));
+        }
+        std::fs::write(&path, csv).map_err(|e| format!("Could not write {}: {}", path, e))?;
+        self.set_status(
+            format!("Exported {} transactions to {}", self.trans_history.items.len(), path),
+            false,
+        );
+        Ok(())
+    }
+
+    pub async fn delete_user(&mut self) {
+        if !self.username.is_empty() {
+            let message = format!("Moved user {} to the trash", self.username);
+            if self.report(&message, delete_user(&self.username).await).is_none() {
+                return;
+            }
+            self.username = "".to_string();
         }
     }
 
     pub async fn submit_message(&mut self) {
+        // stay in the field until its value is acceptable
+        if let Err(e) = self.input_content.validate(&self.input) {
+            self.input_errors.insert(self.input_content.clone(), e);
+            return;
+        }
+        self.input_errors.remove(&self.input_content);
+
         match self.input_content {
             InputContent::Username => {
                 self.username = self.input.clone();
+                set_actor(&self.username);
 
-                // query to create to load user data
-                self.refresh_user_data().await;
+                // query to create the user, then load user data
+                let message = format!("Logged in as {}", self.username);
+                if self.report(&message, query_or_create_user(&self.username).await).is_none() {
+                    self.input_mode = InputMode::Normal;
+                    return;
+                }
+                self.refresh_dashboard().await;
 
                 // rerouting
                 self.input_content = InputContent::AccountName;
                 self.input_mode = InputMode::Normal;
-                self.page = Page::AccountDetails;
+                self.page = Page::Dashboard;
             },
             InputContent::AccountID => self.new_account.acct_id = self.input.clone(),
             InputContent::AccountName => self.new_account.acct_name = self.input.clone(),
             InputContent::AccountType => self.new_account.acct_type = self.input.clone(),
-            InputContent::AccountLimit => self.new_account.card_limit = self.input.clone().parse::<f64>().unwrap_or_else(|_| 0.0),
-            InputContent::TransactionAmount => self.new_trans.amount = self.input.clone().parse::<f64>().unwrap_or_else(|_| 0.0),
+            InputContent::AccountLimit => self.new_account.card_limit = self.input.clone().parse::<f64>().unwrap_or(0.0),
+            InputContent::TransactionAmount => self.new_trans.amount = self.input.clone().parse::<f64>().unwrap_or(0.0),
             InputContent::TransactionCategory => self.new_trans.category = self.input.clone(),
             InputContent::TransactionDescription => self.new_trans.description = self.input.clone(),
+            InputContent::TransactionDate => {
+                if let Some(date) = parse_date_input(&self.input, Local::now().date_naive()) {
+                    self.new_trans.timestamp = date.to_string();
+                }
+            },
             InputContent::TransactionType => self.new_trans.trans_type = self.input.clone(),
             InputContent::FilterTransType => self.filter_trans_type = self.input.clone(),
+            InputContent::TransactionTags => {
+                self.new_trans.tags = self.input
+                    .split(',')
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect();
+            },
+            InputContent::TransactionSplits => self.new_trans.splits = SplitRecord::parse_list(&self.input),
+            InputContent::TransactionAttachment => self.attachment_path = self.input.trim().to_string(),
             InputContent::FilterTransCategory => self.filter_trans_category = self.input.clone(),
+            InputContent::FilterTransTags => self.filter_trans_tags = self.input.clone(),
+            InputContent::FilterTagMatch => self.filter_tag_match = self.input.clone(),
+            InputContent::BudgetCategory => self.new_budget.category = self.input.clone(),
+            InputContent::BudgetAmount => self.new_budget.budgeted = self.input.clone().parse::<f64>().unwrap_or(0.0),
+            InputContent::BudgetAccount => self.new_budget.account_id = self.input.clone(),
+            InputContent::RecurringDescription => self.new_recurring.description = self.input.clone(),
+            InputContent::RecurringType => self.new_recurring.trans_type = self.input.clone(),
+            InputContent::RecurringAmount => self.new_recurring.amount = self.input.clone().parse::<f64>().unwrap_or(0.0),
+            InputContent::RecurringCategory => self.new_recurring.category = self.input.clone(),
+            InputContent::RecurringFrequency => self.new_recurring.frequency = self.input.clone(),
+            InputContent::RecurringStartDate => self.new_recurring.start_date = self.input.clone(),
+            InputContent::RecurringEndDate => self.new_recurring.end_date = self.input.clone(),
         };
         self.input.clear();
+        self.suggestion_idx = 0;
         self.input_mode = InputMode::Normal;
     }
 
+    // Current value of a form field, as it would be typed in
+    pub fn field_value(&self, content: &InputContent) -> String {
+        match content {
+            InputContent::Username => self.username.clone(),
+            InputContent::AccountID => self.new_account.acct_id.clone(),
+            InputContent::AccountName => self.new_account.acct_name.clone(),
+            InputContent::AccountType => self.new_account.acct_type.clone(),
+            InputContent::AccountLimit => self.new_account.card_limit.to_string(),
+            InputContent::TransactionType => self.new_trans.trans_type.clone(),
+            InputContent::TransactionAmount => self.new_trans.amount.to_string(),
+            InputContent::TransactionCategory => self.new_trans.category.clone(),
+            InputContent::TransactionDescription => self.new_trans.description.clone(),
+            InputContent::TransactionDate => self.new_trans.timestamp.clone(),
+            InputContent::TransactionTags => self.new_trans.tags.join(","),
+            InputContent::TransactionSplits => SplitRecord::format_list(&self.new_trans.splits),
+            InputContent::TransactionAttachment => self.attachment_path.clone(),
+            InputContent::FilterTransType => self.filter_trans_type.clone(),
+            InputContent::FilterTransCategory => self.filter_trans_category.clone(),
+            InputContent::FilterTransTags => self.filter_trans_tags.clone(),
+            InputContent::FilterTagMatch => self.filter_tag_match.clone(),
+            InputContent::BudgetCategory => self.new_budget.category.clone(),
+            InputContent::BudgetAmount => self.new_budget.budgeted.to_string(),
+            InputContent::BudgetAccount => self.new_budget.account_id.clone(),
+            InputContent::RecurringDescription => self.new_recurring.description.clone(),
+            InputContent::RecurringType => self.new_recurring.trans_type.clone(),
+            InputContent::RecurringAmount => self.new_recurring.amount.to_string(),
+            InputContent::RecurringCategory => self.new_recurring.category.clone(),
+            InputContent::RecurringFrequency => self.new_recurring.frequency.clone(),
+            InputContent::RecurringStartDate => self.new_recurring.start_date.clone(),
+            InputContent::RecurringEndDate => self.new_recurring.end_date.clone(),
+        }
+    }
+
+    // Checks every field of a form before it is submitted, moving to the
+    // first field that is not acceptable
+    fn validate_form(&mut self, fields: &[InputContent]) -> bool {
+        self.input_errors.clear();
+        for field in fields {
+            if let Err(e) = field.validate(&self.field_value(field)) {
+                self.input_errors.insert(field.clone(), e);
+            }
+        }
+        if let Some(field) = fields.iter().find(|f| self.input_errors.contains_key(f)) {
+            self.input_content = field.clone();
+            self.set_status(String::from("Not saved: fix the highlighted fields"), true);
+            return false;
+        }
+        true
+    }
+
     pub async fn create_new_account(&mut self) {
-        let _ = create_or_update_account(
+        let fields = self.new_acct_question_list[..3].to_vec();
+        if !self.validate_form(&fields) {
+            return;
+        }
+        let message = format!("Created account {}", self.new_account.acct_name);
+        if let Some(acct_id) = self.report(&message, create_or_update_account(
             None,
             self.username.as_str(),
             self.new_account.acct_name.as_str(),
             self.new_account.acct_type.as_str(),
             self.new_account.card_limit
         )
-        .await;
+        .await) {
+            let mut account = self.new_account.clone();
+            account.acct_id = acct_id;
+            self.record_action(UndoAction::CreateAccount(account));
+            self.page = Page::AccountDetails;
+        }
         
         // reload profile data after creating new account
         self.refresh_user_data().await;
     }
 
     pub async fn update_account(&mut self) {
-        let _ = create_or_update_account(
+        let fields = self.new_acct_question_list.clone();
+        if !self.validate_form(&fields) {
+            return;
+        }
+        let before = self.find_account(&self.new_account.acct_id);
+        let message = format!("Saved account {}", self.new_account.acct_name);
+        if self.report(&message, create_or_update_account(
             Some(self.new_account.acct_id.clone()),
             self.username.as_str(),
             self.new_account.acct_name.as_str(),
             self.new_account.acct_type.as_str(),
             self.new_account.card_limit
         )
-        .await;
+        .await)
+        .is_some() {
+            // enter also applies the filters, which leaves the account as it was
+            if let Some(before) = before {
+                if before.acct_name != self.new_account.acct_name || before.card_limit != self.new_account.card_limit {
+                    self.record_action(UndoAction::UpdateAccount { before, after: self.new_account.clone() });
+                }
+            }
+        }
 
         // reload profile data after creating new account
         self.refresh_user_data().await;
     }
 
     pub async fn delete_account(&mut self) {
-        if self.new_account.acct_id != "" {
-            let _ = delete_account(
-                self.new_account.acct_id.parse().unwrap(),
+        if !self.new_account.acct_id.is_empty() {
+            let before = self.find_account(&self.new_account.acct_id);
+            let message = format!("Moved account {} to the trash", self.new_account.acct_name);
+            if self.report(&message, delete_account(
+                self.new_account.acct_id.parse().unwrap_or_default(),
             )
-            .await;
+            .await)
+            .is_some() {
+                if let Some(account) = before {
+                    self.record_action(UndoAction::DeleteAccount(account));
+                }
+            }
             self.new_account.acct_id = "".to_string();
         }
         // reload profile data after creating new account
@@ -297,66 +972,373 @@ impl App {
     }
 
     pub async fn refresh_transactions(&mut self) {
-        if self.new_account.acct_id == "" {
+        if self.new_account.acct_id.is_empty() {
             return;
         }
         let transactions = 
-            if let Ok(trans) = query_account(
-                self.new_account.acct_id.parse().unwrap(),
-                if self.filter_trans_type == "" { None } else { Some(self.filter_trans_type.clone()) },
-                if self.filter_trans_category == "" { None } else { Some(self.filter_trans_category.clone()) },
-            ).await {
+            if let Some(trans) = self.report_error(query_account(
+                self.new_account.acct_id.parse().unwrap_or_default(),
+                if self.filter_trans_type.is_empty() { None } else { Some(self.filter_trans_type.clone()) },
+                if self.filter_trans_category.is_empty() { None } else { Some(self.filter_trans_category.clone()) },
+                if self.filter_trans_tags.is_empty() { None } else { Some(self.filter_trans_tags.clone()) },
+                if self.filter_tag_match.is_empty() { None } else { Some(self.filter_tag_match.clone()) },
+            ).await) {
                 trans
             } else {
                 return;
             };
 
-        // populate loaded transactions
-        self.trans_history.items.clear();
-        self.trans_history.items = transactions.0;
+        // populate loaded transactions, keeping the search
+        self.unsearched_trans = transactions.0;
         self.acct_balance = format!("{:.2}", transactions.1);
+        self.apply_search();
+    }
+
+    // Narrows the loaded transactions down to the ones matching the search.
+    // The whole filtered history is already loaded, so every key filters it
+    // here instead of waiting for the server.
+    pub fn apply_search(&mut self) {
+        let search = self.search_query.trim().to_string();
+        let items = if search.is_empty() {
+            self.unsearched_trans.clone()
+        } else {
+            self.unsearched_trans.iter().filter(|t| t.matches(&search)).cloned().collect()
+        };
+        self.trans_history.items = items;
+        self.trans_history.sort();
+        // the selection would point past the end of a shorter list
+        if self.trans_history.state.selected().is_some_and(|idx| idx >= self.trans_history.items.len()) {
+            self.trans_history.state.select(None);
+        }
+    }
+
+    // Starts typing a search over the transactions of the open account
+    pub fn start_search(&mut self) {
+        self.list_content = ListType::Trans;
+        self.trans_history.state.select(None);
+        self.input_mode = InputMode::Search;
+    }
+
+    pub fn update_search(&mut self, search: String) {
+        self.search_query = search;
+        self.apply_search();
+    }
+
+    pub fn sort_transactions(&mut self, column: TransSortColumn) {
+        let selected_id = self.trans_history.selected_item().map(|record| record.transaction_id.clone());
+        self.trans_history.sort_by(column);
+        // keep the same transaction highlighted in its new position
+        if let Some(id) = selected_id {
+            let idx = self.trans_history.items.iter().position(|t| t.transaction_id == id);
+            self.trans_history.state.select(idx);
+        }
     }
 
     pub async fn create_or_update_transaction(&mut self, create: bool) {
-        let timestamp = if create {
+        let fields = self.new_trans_question_list.clone();
+        if !self.validate_form(&fields) {
+            return;
+        }
+        let timestamp = if self.new_trans.timestamp.is_empty() {
             Local::now().date_naive().to_string()
         } else {
             self.new_trans.timestamp.clone()
         };
+        let before = self.find_transaction(&self.new_trans.transaction_id);
     
-        let trans_id = 
-            if let Ok(tid) = create_or_update_transaction(
+        let message = format!("Saved transaction {}", self.new_trans.description);
+        let (trans_id, budget_warnings) = 
+            if let Some(result) = self.report(&message, create_or_update_transaction(
                 if create { None } else { Some(self.new_trans.transaction_id.clone()) },
                 &timestamp,
                 &self.new_trans.trans_type,
                 &self.new_trans.category,
                 self.new_trans.amount,
                 &self.new_trans.description,
+                &self.new_trans.tags,
+                &self.new_trans.splits,
                 &self.new_account.acct_id,
-            ).await {
-                tid
+            ).await) {
+                result
             } else {
                 return;
             };
     
         self.new_trans.transaction_id = trans_id;
+        self.new_trans.timestamp = timestamp;
+        self.budget_alerts = budget_warnings;
+
+        let account_id = self.new_account.acct_id.clone();
+        let after = self.new_trans.clone();
+        // an edit whose old values aren't loaded can't be undone, and undoing it
+        // as a creation would trash the transaction
+        match before {
+            _ if create => self.record_action(UndoAction::CreateTransaction { account_id, record: after }),
+            Some(before) => self.record_action(UndoAction::UpdateTransaction { account_id, before, after }),
+            None => {}
+        }
+
+        // attach the file once the transaction exists
+        if !self.attachment_path.is_empty() {
+            let message = format!("Saved transaction {} and attached {}", self.new_trans.description, self.attachment_path);
+            self.report(&message, upload_attachment(&self.new_trans.transaction_id, &self.attachment_path).await);
+            self.attachment_path.clear();
+        }
     
         self.refresh_transactions().await;
+        self.refresh_user_data().await;
         self.page = Page::AccountDetails;
     }
 
     pub async fn delete_transaction(&mut self) {
-        if self.new_trans.transaction_id != "" {
-            let _ = delete_transaction(
-                self.new_trans.transaction_id.parse().unwrap(),
+        if !self.new_trans.transaction_id.is_empty() {
+            let record = self.find_transaction(&self.new_trans.transaction_id)
+                .unwrap_or_else(|| self.new_trans.clone());
+            let message = format!("Moved transaction {} to the trash", record.description);
+            if self.report(&message, delete_transaction(
+                self.new_trans.transaction_id.parse().unwrap_or_default(),
             )
-            .await;
+            .await)
+            .is_some() {
+                let account_id = self.new_account.acct_id.clone();
+                self.record_action(UndoAction::DeleteTransaction { account_id, record });
+            }
             self.new_trans.transaction_id = "".to_string();
         }
-        // reload profile data after creating new account
+        // reload transactions and account balances after deleting the transaction
+        self.refresh_transactions().await;
+        self.refresh_user_data().await;
+    }
+
+    fn find_account(&self, acct_id: &str) -> Option<Account> {
+        self.accounts.items.iter().find(|a| a.acct_id == acct_id).cloned()
+    }
+
+    // looks through every loaded transaction, not only the ones the search kept
+    fn find_transaction(&self, transaction_id: &str) -> Option<TransRecord> {
+        self.unsearched_trans.iter().find(|t| t.transaction_id == transaction_id).cloned()
+    }
+
+    // A new change makes the undone ones impossible to replay
+    fn record_action(&mut self, action: UndoAction) {
+        self.undo_stack.push(action);
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    pub async fn undo(&mut self) {
+        if let Some(action) = self.undo_stack.pop() {
+            let result = self.apply_action(&action, true).await;
+            if self.report(&format!("Undid {}", action.describe()), result).is_some() {
+                self.redo_stack.push(action);
+            } else {
+                // keep it so the undo can be retried
+                self.undo_stack.push(action);
+            }
+            self.refresh_after_undo().await;
+        }
+    }
+
+    pub async fn redo(&mut self) {
+        if let Some(action) = self.redo_stack.pop() {
+            let result = self.apply_action(&action, false).await;
+            if self.report(&format!("Redid {}", action.describe()), result).is_some() {
+                self.undo_stack.push(action);
+            } else {
+                self.redo_stack.push(action);
+            }
+            self.refresh_after_undo().await;
+        }
+    }
+
+    // Reverses the action, or replays it when reverse is false. Deleted
+    // entities sit in the server's trash, so creating and deleting are
+    // reversed by moving them in and out of it.
+    async fn apply_action(&self, action: &UndoAction, reverse: bool) -> Result<(), String> {
+        match (action, reverse) {
+            (UndoAction::CreateAccount(account), true) | (UndoAction::DeleteAccount(account), false) => {
+                delete_account(account.acct_id.parse().unwrap_or_default()).await
+            },
+            (UndoAction::CreateAccount(account), false) | (UndoAction::DeleteAccount(account), true) => {
+                restore_from_trash("Account", account.acct_id.parse().unwrap_or_default()).await
+            },
+            (UndoAction::UpdateAccount { before, after }, _) => {
+                let account = if reverse { before } else { after };
+                create_or_update_account(
+                    Some(account.acct_id.clone()),
+                    &self.username,
+                    &account.acct_name,
+                    &account.acct_type,
+                    account.card_limit,
+                )
+                .await
+                .map(|_| ())
+            },
+            (UndoAction::CreateTransaction { record, .. }, true) | (UndoAction::DeleteTransaction { record, .. }, false) => {
+                delete_transaction(record.transaction_id.parse().unwrap_or_default()).await
+            },
+            (UndoAction::CreateTransaction { record, .. }, false) | (UndoAction::DeleteTransaction { record, .. }, true) => {
+                restore_from_trash("Transaction", record.transaction_id.parse().unwrap_or_default()).await
+            },
+            (UndoAction::UpdateTransaction { account_id, before, after }, _) => {
+                let record = if reverse { before } else { after };
+                create_or_update_transaction(
+                    Some(record.transaction_id.clone()),
+                    &record.timestamp,
+                    &record.trans_type,
+                    &record.category,
+                    record.amount,
+                    &record.description,
+                    &record.tags,
+                    &record.splits,
+                    account_id,
+                )
+                .await
+                .map(|_| ())
+            },
+        }
+    }
+
+    // The selected account may have been deleted or brought back to older
+    // values by the undo
+    async fn refresh_after_undo(&mut self) {
+        self.refresh_user_data().await;
+        if let Some(account) = self.find_account(&self.new_account.acct_id) {
+            self.new_account = account;
+            self.refresh_transactions().await;
+        } else {
+            self.new_account.acct_id = "".to_string();
+            self.trans_history.items.clear();
+            self.unsearched_trans.clear();
+        }
+    }
+
+    pub async fn refresh_budgets(&mut self) {
+        let budgets =
+            if let Some(b) = self.report_error(query_budgets(&self.username).await) {
+                b
+            } else {
+                return;
+            };
+        self.budgets.items = budgets;
+    }
+
+    pub fn clear_budget_form(&mut self) {
+        self.new_budget = Budget::new("", "", "", 0.0);
+        self.input_errors.clear();
+        self.input_content = InputContent::BudgetCategory;
+    }
+
+    pub async fn save_budget(&mut self) {
+        let fields = self.new_budget_question_list.clone();
+        if !self.validate_form(&fields) {
+            return;
+        }
+        let message = format!("Saved budget for {}", self.new_budget.category);
+        let result = create_or_update_budget(
+            if self.new_budget.budget_id.is_empty() { None } else { Some(self.new_budget.budget_id.clone()) },
+            self.username.as_str(),
+            self.new_budget.category.as_str(),
+            if self.new_budget.account_id.is_empty() { None } else { Some(self.new_budget.account_id.clone()) },
+            self.new_budget.budgeted,
+        )
+        .await;
+        // keep the form so a rejected budget can be corrected
+        if self.report(&message, result).is_none() {
+            return;
+        }
+
+        // reload budgets to pick up the spending of the saved budget
+        self.clear_budget_form();
+        self.refresh_budgets().await;
+    }
+
+    pub async fn delete_budget(&mut self) {
+        if !self.new_budget.budget_id.is_empty() {
+            let message = format!("Deleted budget for {}", self.new_budget.category);
+            let result = delete_budget(
+                self.new_budget.budget_id.parse().unwrap_or_default(),
+            )
+            .await;
+            self.report(&message, result);
+        }
+        self.clear_budget_form();
+        self.refresh_budgets().await;
+    }
+
+    pub async fn refresh_recurring(&mut self) {
+        let recurring =
+            if let Some(r) = self.report_error(query_recurring(&self.username).await) {
+                r
+            } else {
+                return;
+            };
+        self.recurring.items = recurring;
+    }
+
+    // new schedules start today on the selected account
+    pub fn clear_recurring_form(&mut self) {
+        self.new_recurring = RecurringRecord {
+            account_id: self.new_account.acct_id.clone(),
+            account_name: self.new_account.acct_name.clone(),
+            frequency: RECURRING_FREQUENCIES[2].to_string(),
+            start_date: Local::now().date_naive().to_string(),
+            ..RecurringRecord::default()
+        };
+        self.input_errors.clear();
+        self.input_content = InputContent::RecurringDescription;
+    }
+
+    pub async fn save_recurring(&mut self) {
+        let fields = self.new_recurring_question_list.clone();
+        if !self.validate_form(&fields) {
+            return;
+        }
+        let message = format!("Saved schedule {}", self.new_recurring.description);
+        let result = create_or_update_recurring(&self.new_recurring).await;
+        if self.report(&message, result).is_none() {
+            return;
+        }
+
+        // the scheduler creates the due transactions, so reload them as well
+        self.clear_recurring_form();
+        self.refresh_recurring().await;
         self.refresh_transactions().await;
     }
 
+    pub async fn toggle_recurring_paused(&mut self) {
+        if !self.new_recurring.recurring_id.is_empty() {
+            let message = if self.new_recurring.paused {
+                format!("Resumed schedule {}", self.new_recurring.description)
+            } else {
+                format!("Paused schedule {}", self.new_recurring.description)
+            };
+            let result = pause_recurring(
+                self.new_recurring.recurring_id.parse().unwrap_or_default(),
+                !self.new_recurring.paused,
+            )
+            .await;
+            if self.report(&message, result).is_some() {
+                self.new_recurring.paused = !self.new_recurring.paused;
+            }
+        }
+        self.refresh_recurring().await;
+    }
+
+    pub async fn delete_recurring(&mut self) {
+        if !self.new_recurring.recurring_id.is_empty() {
+            let message = format!("Deleted schedule {}", self.new_recurring.description);
+            let result = delete_recurring(
+                self.new_recurring.recurring_id.parse().unwrap_or_default(),
+            )
+            .await;
+            self.report(&message, result);
+        }
+        self.clear_recurring_form();
+        self.refresh_recurring().await;
+    }
 
     // LIST RELATED FUNCTIONS
     pub fn select_first(&mut self) {
@@ -366,6 +1348,17 @@ impl App {
             },
             ListType::Trans => {
                 self.trans_history.state.select_first();
+            },
+            ListType::Recurring => {
+                self.recurring.state.select_first();
+            },
+            ListType::Budget => {
+                // budgets are not rendered as a List, which would clamp the selection
+                if self.budgets.items.is_empty() {
+                    self.budgets.state.select(None);
+                } else {
+                    self.budgets.state.select_first();
+                }
             }
         }
         self.input_mode = InputMode::ViewAccountList;
@@ -378,6 +1371,16 @@ impl App {
             },
             ListType::Trans => {
                 self.trans_history.state.select_next();
+            },
+            ListType::Recurring => {
+                self.recurring.state.select_next();
+            },
+            ListType::Budget => {
+                if let Some(idx) = self.budgets.state.selected() {
+                    if idx + 1 < self.budgets.items.len() {
+                        self.budgets.state.select(Some(idx + 1));
+                    }
+                }
             }
         }
     }
@@ -389,26 +1392,71 @@ impl App {
             },
             ListType::Trans => {
                 self.trans_history.state.select_previous();
+            },
+            ListType::Budget => {
+                self.budgets.state.select_previous();
+            },
+            ListType::Recurring => {
+                self.recurring.state.select_previous();
             }
         }
     }
 
     pub fn confirm_selection(&mut self) {
+        self.input_errors.clear();
         match self.list_content {
             ListType::Acct => {
-                let idx = self.accounts.state.selected().unwrap();
-                self.new_account = self.accounts.items[idx].clone();
+                let Some(account) = self.accounts.state.selected().and_then(|i| self.accounts.items.get(i)) else {
+                    return;
+                };
+                self.new_account = account.clone();
             },
             ListType::Trans => {
-                let idx = self.trans_history.state.selected().unwrap();
-                self.new_trans = self.trans_history.items[idx].clone();
+                let Some(record) = self.trans_history.selected_item() else {
+                    return;
+                };
+                self.new_trans = record.clone();
                 self.input_content = InputContent::TransactionDescription;
                 self.input_mode = InputMode::Normal;
                 self.page = Page::EditTransaction;
+            },
+            ListType::Budget => {
+                if let Some(budget) = self.budgets.state.selected().and_then(|i| self.budgets.items.get(i)) {
+                    self.new_budget = budget.clone();
+                }
+                self.budgets.state.select(None);
+                self.input_content = InputContent::BudgetCategory;
+                self.input_mode = InputMode::Normal;
+            },
+            ListType::Recurring => {
+                if let Some(recurring) = self.recurring.state.selected().and_then(|i| self.recurring.items.get(i)) {
+                    self.new_recurring = recurring.clone();
+                }
+                self.recurring.state.select(None);
+                self.input_content = InputContent::RecurringDescription;
+                self.input_mode = InputMode::Normal;
             }
         }
     }
 
+    pub fn select_at(&mut self, idx: usize) {
+        match self.list_content {
+            ListType::Acct => {
+                self.accounts.state.select(Some(idx));
+            },
+            ListType::Trans => {
+                self.trans_history.state.select(Some(idx));
+            },
+            ListType::Budget => {
+                self.budgets.state.select(Some(idx));
+            },
+            ListType::Recurring => {
+                self.recurring.state.select(Some(idx));
+            }
+        }
+        self.input_mode = InputMode::ViewAccountList;
+    }
+
     pub fn stop_select(&mut self) {
         match self.list_content {
             ListType::Acct => {
@@ -416,12 +1464,18 @@ impl App {
             },
             ListType::Trans => {
                 self.trans_history.state.select(None);
+            },
+            ListType::Budget => {
+                self.budgets.state.select(None);
+            },
+            ListType::Recurring => {
+                self.recurring.state.select(None);
             }
         }
         self.input_mode = InputMode::Normal;
     }
 
-    pub fn find_index(vec: &Vec<InputContent>, target: InputContent) -> i32 {
+    pub fn find_index(vec: &[InputContent], target: InputContent) -> i32 {
         // Find the index of the target element
         if let Some(index) = vec.iter().position(|x| *x == target) {
             // Return the next element, if it exists
@@ -432,7 +1486,7 @@ impl App {
         }
     }
 
-    pub fn find_next_index(vec: &Vec<InputContent>, target: InputContent) -> i32 {
+    pub fn find_next_index(vec: &[InputContent], target: InputContent) -> i32 {
         // Find the index of the target element
         let index = App::find_index(vec, target);
         if index == -1 {
@@ -444,7 +1498,7 @@ impl App {
         }
     }
 
-    pub fn find_prev_index(vec: &Vec<InputContent>, target: InputContent) -> i32 {
+    pub fn find_prev_index(vec: &[InputContent], target: InputContent) -> i32 {
         // Find the index of the target element
         let index = App::find_index(vec, target);
         if index == -1 {
@@ -467,6 +1521,12 @@ impl App {
             Page::NewTransaction | Page::EditTransaction => {
                 self.new_trans_question_list.clone()
             },
+            Page::Budgets => {
+                self.new_budget_question_list.clone()
+            },
+            Page::Recurring => {
+                self.new_recurring_question_list.clone()
+            },
             _ => Vec::new()
         };
         let index = App::find_next_index(&question_list, self.input_content.clone());
@@ -484,17 +1544,44 @@ impl App {
             Page::NewTransaction | Page::EditTransaction => {
                 self.new_trans_question_list.clone()
             },
+            Page::Budgets => {
+                self.new_budget_question_list.clone()
+            },
+            Page::Recurring => {
+                self.new_recurring_question_list.clone()
+            },
             _ => Vec::new()
         };
         let index = App::find_prev_index(&question_list, self.input_content.clone());
         self.input_content = question_list[index as usize].clone();
     }
 
-    const fn alternate_colors(i: usize) -> Color {
-        if i % 2 == 0 {
-            NORMAL_ROW_BG
+    // MOUSE RELATED FUNCTIONS
+    pub fn target_at(&self, column: u16, row: u16) -> Option<ClickTarget> {
+        self.hit_regions
+            .iter()
+            .rev()
+            .find(|(area, _)| area.contains(Position::new(column, row)))
+            .map(|(_, target)| target.clone())
+    }
+
+    // The list as a whole and each visible row, one line per item
+    fn register_list(&mut self, list: ListType, area: Rect, offset: usize, len: usize) {
+        self.hit_regions.push((area, ClickTarget::List(list.clone())));
+        let visible = (area.height as usize).min(len.saturating_sub(offset));
+        for row in 0..visible {
+            self.hit_regions.push((
+                Rect::new(area.x, area.y + row as u16, area.width, 1),
+                ClickTarget::Item(list.clone(), offset + row),
+            ));
+        }
+    }
+
+    fn alternate_colors(&self, i: usize) -> Color {
+        if i.is_multiple_of(2) {
+            self.theme.row_bg
         } else {
-            ALT_ROW_BG_COLOR
+            self.theme.alt_row_bg
         }
     }
 
@@ -505,8 +1592,9 @@ impl App {
             .title(Line::raw("Associated Accounts").centered())
             .borders(Borders::TOP)
             .border_set(symbols::border::EMPTY)
-            .border_style(TODO_HEADER_STYLE)
-            .bg(NORMAL_ROW_BG);
+            .border_style(self.theme.header())
+            .bg(self.theme.row_bg);
+        let inner = block.inner(area);
 
         let items: Vec<ListItem> = self
             .accounts
@@ -514,48 +1602,205 @@ impl App {
             .iter()
             .enumerate()
             .map(|(i, todo_item)| {
-                let color = App::alternate_colors(i);
-                ListItem::from(todo_item).bg(color)
+                let color = self.alternate_colors(i);
+                todo_item.to_list_item(&self.theme).bg(color)
             })
             .collect();
 
         let list = List::new(items)
             .block(block)
-            .highlight_style(SELECTED_STYLE)
+            .highlight_style(self.theme.selected())
             .highlight_symbol(">")
             .highlight_spacing(HighlightSpacing::Always);
 
         StatefulWidget::render(list, area, buf, &mut self.accounts.state);
+        self.register_list(ListType::Acct, inner, self.accounts.state.offset(), self.accounts.items.len());
     }
 
-    // transaction list 
+    // transaction table, with the sort column marked in the header
     pub fn render_trans_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let title = if self.input_mode == InputMode::Search {
+            format!("Transaction Records - search: {}_", self.search_query)
+        } else if !self.search_query.is_empty() {
+            format!("Transaction Records - {} matching \"{}\"", self.trans_history.items.len(), self.search_query)
+        } else {
+            String::from("Transaction Records")
+        };
         let block = Block::new()
-            .title(Line::raw("Transaction Records").centered())
+            .title(Line::raw(title).centered())
             .borders(Borders::TOP)
             .border_set(symbols::border::EMPTY)
-            .border_style(TODO_HEADER_STYLE)
-            .bg(NORMAL_ROW_BG);
+            .border_style(self.theme.header())
+            .bg(self.theme.row_bg);
+        let inner = block.inner(area);
 
-        let items: Vec<ListItem> = self
+        let arrow = if self.trans_history.sort_descending { "v" } else { "^" };
+        let mut header: Vec<String> = TRANS_SORT_COLUMNS
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                if *column == self.trans_history.sort_column {
+                    format!("{} {} {}", i + 1, column.title(), arrow)
+                } else {
+                    format!("{} {}", i + 1, column.title())
+                }
+            })
+            .collect();
+        header.push(String::from("Balance"));
+
+        let rows: Vec<Row> = self
             .trans_history
             .items
             .iter()
             .enumerate()
+            .map(|(i, record)| {
+                let color = self.alternate_colors(i);
+                record.to_row(&self.theme, &self.search_query).bg(color)
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ];
+        let table = Table::new(rows, widths)
+            .header(Row::new(header).bold())
+            .block(block)
+            .row_highlight_style(self.theme.selected())
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+
+        StatefulWidget::render(table, area, buf, &mut self.trans_history.state);
+        // rows start below the header
+        let rows_area = Rect::new(inner.x, inner.y + 1, inner.width, inner.height.saturating_sub(1));
+        self.register_list(ListType::Trans, rows_area, self.trans_history.state.offset(), self.trans_history.items.len());
+    }
+
+    // everything about the highlighted transaction, including what does not
+    // fit in the table
+    pub fn render_trans_detail(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::new()
+            .title(Line::raw("Transaction Details").centered())
+            .borders(Borders::TOP)
+            .border_set(symbols::border::EMPTY)
+            .border_style(self.theme.header())
+            .bg(self.theme.row_bg);
+
+        let Some(record) = self.trans_history.selected_item() else {
+            Paragraph::new(format!("Press {} to select a transaction", self.keymap.describe(Action::SelectTransactions)))
+                .block(block)
+                .wrap(Wrap { trim: true })
+                .render(area, buf);
+            return;
+        };
+
+        let mut lines = vec![
+            Line::from(format!("ID: {}", record.transaction_id)),
+            Line::from(format!("Date: {}", record.timestamp)),
+            Line::from(format!("Type: {}", record.trans_type)),
+            Line::from(format!("Category: {}", record.category)),
+            Line::from(format!("Amount: {:.2}", record.amount)),
+            Line::from(format!("Balance after: {:.2}", record.balance)),
+            Line::from(format!("Memo: {}", record.description)),
+        ];
+        if !record.tags.is_empty() {
+            lines.push(Line::from(format!("Tags: {}", record.tags.join(", "))));
+        }
+        if !record.splits.is_empty() {
+            lines.push(Line::from("Splits:"));
+            for split in record.splits.iter() {
+                if split.memo.is_empty() {
+                    lines.push(Line::from(format!("  {}: {:.2}", split.category, split.amount)));
+                } else {
+                    lines.push(Line::from(format!("  {}: {:.2} ({})", split.category, split.amount, split.memo)));
+                }
+            }
+        }
+        if record.attachment_count > 0 {
+            lines.push(Line::from(format!("Attachments: {}", record.attachment_count)));
+        }
+
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+
+    // recurring transaction list
+    pub fn render_recurring_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::new()
+            .title(Line::raw("Recurring Transactions").centered())
+            .borders(Borders::TOP)
+            .border_set(symbols::border::EMPTY)
+            .border_style(self.theme.header())
+            .bg(self.theme.row_bg);
+        let inner = block.inner(area);
+
+        let items: Vec<ListItem> = self
+            .recurring
+            .items
+            .iter()
+            .enumerate()
             .map(|(i, todo_item)| {
-                let color = App::alternate_colors(i);
-                ListItem::from(todo_item).bg(color)
+                let color = self.alternate_colors(i);
+                todo_item.to_list_item(&self.theme).bg(color)
             })
             .collect();
 
         let list = List::new(items)
             .block(block)
-            .highlight_style(SELECTED_STYLE)
+            .highlight_style(self.theme.selected())
             .highlight_symbol(">")
             .highlight_spacing(HighlightSpacing::Always);
 
-        StatefulWidget::render(list, area, buf, &mut self.trans_history.state);
+        StatefulWidget::render(list, area, buf, &mut self.recurring.state);
+        self.register_list(ListType::Recurring, inner, self.recurring.state.offset(), self.recurring.items.len());
     }
-    
 
+    // budget gauges, one row per budget
+    pub fn render_budget_gauges(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::new()
+            .title(Line::raw("Monthly Budgets").centered())
+            .borders(Borders::TOP)
+            .border_set(symbols::border::EMPTY)
+            .border_style(self.theme.header())
+            .bg(self.theme.row_bg);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // scroll so that the selected budget stays visible
+        let visible = inner.height as usize;
+        let selected = self.budgets.state.selected();
+        let offset = selected.map(|i| (i + 1).saturating_sub(visible)).unwrap_or(0);
+        self.register_list(ListType::Budget, inner, offset, self.budgets.items.len());
+
+        for (row, (i, budget)) in self.budgets.items.iter().enumerate().skip(offset).take(visible).enumerate() {
+            let ratio = if budget.budgeted > 0.0 {
+                (budget.actual / budget.budgeted).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            let color = if budget.remaining < 0.0 { self.theme.error } else { self.theme.success };
+            let scope = if budget.account_id.is_empty() {
+                "all accounts".to_string()
+            } else {
+                format!("account {}", budget.account_id)
+            };
+            let label = format!(
+                "{} ({}): {:.2} / {:.2}, {:.2} left ",
+                budget.category, scope, budget.actual, budget.budgeted, budget.remaining
+            );
+            let label_style = if selected == Some(i) { self.theme.selected() } else { Style::default() };
+
+            LineGauge::default()
+                .filled_style(Style::new().fg(color))
+                .ratio(ratio)
+                .label(Span::styled(label, label_style))
+                .render(Rect::new(inner.x, inner.y + row as u16, inner.width, 1), buf);
+        }
+    }
 }
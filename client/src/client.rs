@@ -1,9 +1,80 @@
-use serde::{Deserialize, Serialize};
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::RwLock;
 
-use crate::input::{Account, TransRecord};
+use crate::input::{Account, Budget, CategoryUsage, CreditUtilization, Dashboard, NetWorth, RecurringRecord, SpendingCharts, SplitRecord, TransRecord};
 
 const SERVER_BASE_URL: &str = "http://localhost:8080";
 
+// user reported to the server as the author of every change
+static ACTOR: RwLock<String> = RwLock::new(String::new());
+
+pub fn set_actor(username: &str) {
+    if let Ok(mut actor) = ACTOR.write() {
+        *actor = username.to_string();
+    }
+}
+
+fn new_client() -> reqwest::Client {
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Ok(actor) = ACTOR.read() {
+        if let Ok(value) = reqwest::header::HeaderValue::from_str(&actor) {
+            headers.insert("X-Actor", value);
+        }
+    }
+    reqwest::Client::builder()
+        .default_headers(headers)
+        .build()
+        .unwrap_or_default()
+}
+
+// Sends a request and returns the response body, turning a failure into a
+// message for the status bar: the server being unreachable, or the error it
+// answered with
+async fn send(request: reqwest::RequestBuilder) -> Result<String, String> {
+    let resp = request.send().await.map_err(|e| {
+        if e.is_connect() {
+            String::from("Connection refused: is the server running?")
+        } else if e.is_timeout() {
+            String::from("Request timed out")
+        } else {
+            format!("Network error: {}", e)
+        }
+    })?;
+    let status = resp.status();
+    let body = resp.text().await.map_err(|e| format!("Network error: {}", e))?;
+    if status.is_success() {
+        return Ok(body);
+    }
+
+    // errors come back as a JSON string prefixed with "Error: ", except the
+    // plain text ones raised while parsing the request
+    let message = serde_json::from_str::<String>(&body).unwrap_or(body);
+    let message = message.trim_start_matches("Error: ");
+    Err(match status {
+        StatusCode::NOT_FOUND => format!("Not found: {}", message),
+        StatusCode::BAD_REQUEST => format!("Rejected: {}", message),
+        _ => format!("Server error ({}): {}", status.as_u16(), message),
+    })
+}
+
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, String> {
+    serde_json::from_str(body).map_err(|e| format!("Unexpected response from server: {}", e))
+}
+
+// Percent-encodes free text typed by the user, so characters such as & and +
+// reach the server as they were typed
+fn encode_form_value(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
 #[derive(Serialize, Deserialize)]
 struct ServerAccount {
     account_id: i64,
@@ -25,6 +96,173 @@ impl ServerAccount {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+struct ServerAccountBalance {
+    account_id: i64,
+    user_id: i64,
+    account_name: String,
+    account_type: String,
+    account_limit: f64,
+    balance: f64,
+    transaction_count: i64,
+}
+
+impl ServerAccountBalance {
+    fn to_account(&self) -> Account {
+        let mut account = Account::new(
+            &format!("{}", self.account_id),
+            &self.account_name,
+            &format!("{}", self.user_id),
+            &self.account_type,
+            self.account_limit,
+        );
+        account.balance = self.balance;
+        account.transaction_count = self.transaction_count;
+        account
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerUserSummary {
+    accounts: Vec<ServerAccountBalance>,
+    total_assets: f64,
+    total_liabilities: f64,
+    net_worth: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerNetWorthPoint {
+    period_date: String,
+    net_worth: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerCategorySpending {
+    category_name: String,
+    spent: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerMonthlyFlow {
+    month: String,
+    income: f64,
+    expenses: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerBalancePoint {
+    balance_date: String,
+    balance: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerSpendingCharts {
+    by_category: Vec<ServerCategorySpending>,
+    monthly: Vec<ServerMonthlyFlow>,
+    balance: Vec<ServerBalancePoint>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerCreditUtilization {
+    account_id: i64,
+    account_name: String,
+    owed: f64,
+    account_limit: f64,
+    utilization: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerDashboard {
+    summary: ServerUserSummary,
+    month: ServerMonthlyFlow,
+    top_categories: Vec<ServerCategorySpending>,
+    upcoming_recurring: Vec<ServerRecurring>,
+    credit_utilization: Vec<ServerCreditUtilization>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerBudgetStatus {
+    budget_id: i64,
+    category_id: i64,
+    category_name: String,
+    account_id: Option<i64>,
+    budgeted: f64,
+    actual: f64,
+    remaining: f64,
+}
+
+impl ServerBudgetStatus {
+    fn to_budget(&self) -> Budget {
+        let mut budget = Budget::new(
+            &format!("{}", self.budget_id),
+            &self.category_name,
+            &self.account_id.map(|a| a.to_string()).unwrap_or_default(),
+            self.budgeted,
+        );
+        budget.actual = self.actual;
+        budget.remaining = self.remaining;
+        budget
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerRecurring {
+    recurring_id: i64,
+    account_id: i64,
+    account_name: String,
+    transaction_type: String,
+    category: String,
+    amount: f64,
+    transaction_memo: String,
+    frequency: String,
+    start_date: String,
+    end_date: Option<String>,
+    next_date: String,
+    paused: bool,
+}
+
+impl ServerRecurring {
+    fn to_recurring(&self) -> RecurringRecord {
+        RecurringRecord {
+            recurring_id: format!("{}", self.recurring_id),
+            account_id: format!("{}", self.account_id),
+            account_name: self.account_name.clone(),
+            trans_type: self.transaction_type.clone(),
+            category: self.category.clone(),
+            description: self.transaction_memo.clone(),
+            amount: self.amount,
+            frequency: self.frequency.clone(),
+            start_date: self.start_date.clone(),
+            end_date: self.end_date.clone().unwrap_or_default(),
+            next_date: self.next_date.clone(),
+            paused: self.paused,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerCategoryUsage {
+    category_name: String,
+    use_count: i64,
+    last_used: Option<String>,
+}
+
+impl ServerCategoryUsage {
+    fn to_category_usage(&self) -> CategoryUsage {
+        CategoryUsage {
+            name: self.category_name.clone(),
+            use_count: self.use_count,
+            last_used: self.last_used.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerTransactionResult {
+    transaction_id: i64,
+    budget_warnings: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct ServerTransaction {
     transaction_id: i64,
@@ -33,18 +271,52 @@ struct ServerTransaction {
     category: String,
     transaction_memo: String,
     amount: f64,
+    tags: Vec<String>,
+    splits: Vec<ServerSplit>,
+    attachment_count: i64,
+    balance: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServerSplit {
+    category: String,
+    amount: f64,
+    split_memo: String,
+}
+
+impl ServerSplit {
+    fn from_split(split: &SplitRecord) -> Self {
+        Self {
+            category: split.category.clone(),
+            amount: split.amount,
+            split_memo: split.memo.clone(),
+        }
+    }
+
+    fn to_split(&self) -> SplitRecord {
+        SplitRecord {
+            category: self.category.clone(),
+            amount: self.amount,
+            memo: self.split_memo.clone(),
+        }
+    }
 }
 
 impl ServerTransaction {
     fn to_transaction(&self) -> TransRecord {
-        TransRecord::new(
+        let mut transaction = TransRecord::new(
             &format!("{}", self.transaction_id),
             &self.transaction_date,
             &self.transaction_type,
             &self.category,
             &self.transaction_memo,
             self.amount,
-        )
+        );
+        transaction.tags = self.tags.clone();
+        transaction.splits = self.splits.iter().map(|s| s.to_split()).collect();
+        transaction.attachment_count = self.attachment_count;
+        transaction.balance = self.balance;
+        transaction
     }
 }
 
@@ -55,20 +327,13 @@ impl ServerTransaction {
 // }
 pub async fn query_or_create_user(username: &str) -> Result<Vec<Account>, String> {
     let url = format!("{SERVER_BASE_URL}/query_or_create_user");
-    let client = reqwest::Client::new();
-    let resp = client
+    let client = new_client();
+    let request = client
         .post(&url)
         .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
-        .body(format!("username={username}"))
-        .send()
-        .await
-        .unwrap();
-    if !resp.status().is_success() {
-        return Err(String::from("Error: Reqwest failed"));
-    }
-
-    let body = resp.text().await.unwrap();
-    let accounts: Vec<ServerAccount> = serde_json::from_str(&body).unwrap();
+        .body(format!("username={}", encode_form_value(username)));
+    let body = send(request).await?;
+    let accounts: Vec<ServerAccount> = parse(&body)?;
     let accounts = accounts
         .iter()
         .map(|a| a.to_account())
@@ -77,6 +342,150 @@ pub async fn query_or_create_user(username: &str) -> Result<Vec<Account>, String
     Ok(accounts)
 }
 
+// Example usage:
+// let (accounts, net_worth) = crate::client::query_user_summary("sophie").await?;
+pub async fn query_user_summary(username: &str) -> Result<(Vec<Account>, NetWorth), String> {
+    let url = format!("{SERVER_BASE_URL}/query_user_summary");
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(format!("username={}", encode_form_value(username)));
+    let body = send(request).await?;
+    let summary: ServerUserSummary = parse(&body)?;
+    let accounts = summary
+        .accounts
+        .iter()
+        .map(|a| a.to_account())
+        .collect::<Vec<Account>>();
+    let net_worth = NetWorth {
+        total_assets: summary.total_assets,
+        total_liabilities: summary.total_liabilities,
+        net_worth: summary.net_worth,
+    };
+
+    Ok((accounts, net_worth))
+}
+
+// The user's categories, the ones used the most and the most lately first
+// Example usage:
+// let categories = crate::client::query_category_usage("sophie").await?;
+pub async fn query_category_usage(username: &str) -> Result<Vec<CategoryUsage>, String> {
+    let url = format!("{SERVER_BASE_URL}/query_category_usage");
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(format!("username={}", encode_form_value(username)));
+    let body = send(request).await?;
+    let usage: Vec<ServerCategoryUsage> = parse(&body)?;
+    Ok(usage.iter().map(|c| c.to_category_usage()).collect())
+}
+
+// Everything on the home page in one call
+// Example usage:
+// let (accounts, net_worth, dashboard) = crate::client::query_dashboard("sophie").await?;
+pub async fn query_dashboard(username: &str) -> Result<(Vec<Account>, NetWorth, Dashboard), String> {
+    let url = format!("{SERVER_BASE_URL}/query_dashboard");
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(format!("username={}", encode_form_value(username)));
+    let body = send(request).await?;
+    let dashboard: ServerDashboard = parse(&body)?;
+    let accounts = dashboard
+        .summary
+        .accounts
+        .iter()
+        .map(|a| a.to_account())
+        .collect::<Vec<Account>>();
+    let net_worth = NetWorth {
+        total_assets: dashboard.summary.total_assets,
+        total_liabilities: dashboard.summary.total_liabilities,
+        net_worth: dashboard.summary.net_worth,
+    };
+
+    Ok((accounts, net_worth, Dashboard {
+        month: dashboard.month.month,
+        income: dashboard.month.income,
+        expenses: dashboard.month.expenses,
+        top_categories: dashboard.top_categories.into_iter().map(|c| (c.category_name, c.spent)).collect(),
+        upcoming_recurring: dashboard.upcoming_recurring.iter().map(|r| r.to_recurring()).collect(),
+        credit_utilization: dashboard
+            .credit_utilization
+            .into_iter()
+            .map(|c| CreditUtilization {
+                acct_id: format!("{}", c.account_id),
+                acct_name: c.account_name,
+                owed: c.owed,
+                card_limit: c.account_limit,
+                utilization: c.utilization,
+            })
+            .collect(),
+    }))
+}
+
+// Example usage:
+// let history = crate::client::query_net_worth_history("sophie", "Monthly").await?;
+pub async fn query_net_worth_history(username: &str, granularity: &str) -> Result<Vec<(String, f64)>, String> {
+    let url = format!("{SERVER_BASE_URL}/query_net_worth_history");
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(format!("username={}&granularity={granularity}", encode_form_value(username)));
+    let body = send(request).await?;
+    let points: Vec<ServerNetWorthPoint> = parse(&body)?;
+    Ok(points
+        .into_iter()
+        .map(|p| (p.period_date, p.net_worth))
+        .collect::<Vec<(String, f64)>>())
+}
+
+// Example usage:
+// let charts = crate::client::query_spending_charts(2, None, None, None, None, Some("2024-01-01".to_string())).await?;
+pub async fn query_spending_charts(
+    account_id: i64,
+    trans_type: Option<String>,
+    category: Option<String>,
+    tags: Option<String>,
+    tag_match: Option<String>,
+    start_date: Option<String>,
+) -> Result<SpendingCharts, String> {
+    let url = format!("{SERVER_BASE_URL}/query_spending_charts");
+
+    let mut post_body = format!("account_id={account_id}");
+    if let Some(ttype) = trans_type {
+        post_body.push_str(&format!("&transaction_type={ttype}"));
+    }
+    if let Some(tcate) = category {
+        post_body.push_str(&format!("&category={}", encode_form_value(&tcate)));
+    }
+    if let Some(ttags) = tags {
+        post_body.push_str(&format!("&tags={}", encode_form_value(&ttags)));
+    }
+    if let Some(tmatch) = tag_match {
+        post_body.push_str(&format!("&tag_match={tmatch}"));
+    }
+    if let Some(start) = start_date {
+        post_body.push_str(&format!("&start_date={start}"));
+    }
+
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(post_body);
+    let body = send(request).await?;
+    let charts: ServerSpendingCharts = parse(&body)?;
+    Ok(SpendingCharts {
+        by_category: charts.by_category.into_iter().map(|c| (c.category_name, c.spent)).collect(),
+        monthly: charts.monthly.into_iter().map(|m| (m.month, m.income, m.expenses)).collect(),
+        balance: charts.balance.into_iter().map(|b| (b.balance_date, b.balance)).collect(),
+    })
+}
+
 // Example usage:
 // let acct_id_str = crate::client::create_or_update_account(Some("2".to_string()), "sophie", "account2", "Credit", 2000.0).await;
 pub async fn create_or_update_account(acct_id: Option<String>,
@@ -90,35 +499,31 @@ pub async fn create_or_update_account(acct_id: Option<String>,
     } else {
         "".to_string()
     };
-    post_body.push_str(&format!("username={}&", username));
-    post_body.push_str(&format!("account_name={}&", acct_name));
+    post_body.push_str(&format!("username={}&", encode_form_value(username)));
+    post_body.push_str(&format!("account_name={}&", encode_form_value(acct_name)));
     post_body.push_str(&format!("account_type={}&", acct_type));
     post_body.push_str(&format!("account_limit={}", card_limit));
 
-    let client = reqwest::Client::new();
-    let resp = client
+    let client = new_client();
+    let request = client
         .post(&url)
         .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
-        .body(post_body)
-        .send()
-        .await
-        .unwrap();
-    if !resp.status().is_success() {
-        return Err(String::from("Error: Reqwest failed"));
-    }
-
-    Ok(resp.text().await.unwrap())
+        .body(post_body);
+    send(request).await
 }
 
 // Example usage:
-// let trans_id_str = crate::client::create_or_update_transaction(Some("1".to_string()), "2024-11-11", "Expenses", "Meal", 13.3, "Sushi Burrito", "1").await;
+// let (trans_id_str, budget_warnings) = crate::client::create_or_update_transaction(Some("1".to_string()), "2024-11-11", "Expenses", "Meal", 13.3, "Sushi Burrito", "1").await?;
+#[allow(clippy::too_many_arguments)]
 pub async fn create_or_update_transaction(trans_id: Option<String>,
                                           timestamp: &str,
                                           trans_type: &str,
                                           category: &str,
                                           amt: f64,
                                           descrip: &str,
-                                          acct_id: &str) -> Result<String, String> {
+                                          tags: &[String],
+                                          splits: &[SplitRecord],
+                                          acct_id: &str) -> Result<(String, Vec<String>), String> {
 
     let url = format!("{SERVER_BASE_URL}/create_or_update_transaction");
     let mut post_body = if let Some(tid) = trans_id {
@@ -128,71 +533,204 @@ pub async fn create_or_update_transaction(trans_id: Option<String>,
     };
     post_body.push_str(&format!("transaction_date={}&", timestamp));
     post_body.push_str(&format!("transaction_type={}&", trans_type));
-    post_body.push_str(&format!("category={}&", category));
+    post_body.push_str(&format!("category={}&", encode_form_value(category)));
     post_body.push_str(&format!("amount={}&", amt));
-    post_body.push_str(&format!("transaction_memo={}&", descrip));
+    post_body.push_str(&format!("transaction_memo={}&", encode_form_value(descrip)));
+    post_body.push_str(&format!("tags={}&", encode_form_value(&tags.join(","))));
+    let splits: Vec<ServerSplit> = splits.iter().map(ServerSplit::from_split).collect();
+    post_body.push_str(&format!("splits={}&", encode_form_value(&serde_json::to_string(&splits).unwrap_or_default())));
     post_body.push_str(&format!("account_id={}", acct_id));
 
-    let client = reqwest::Client::new();
-    let resp = client
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(post_body);
+    let body = send(request).await?;
+    let result: ServerTransactionResult = parse(&body)?;
+    Ok((format!("{}", result.transaction_id), result.budget_warnings))
+}
+
+// Example usage:
+// crate::client::upload_attachment("12", "/home/sophie/receipt.pdf").await?;
+pub async fn upload_attachment(trans_id: &str, file_path: &str) -> Result<(), String> {
+    let content = std::fs::read(file_path).map_err(|e| format!("Error: {}", e))?;
+    let file_name = std::path::Path::new(file_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let url = format!("{SERVER_BASE_URL}/upload_attachment/{trans_id}?file_name={}", encode_form_value(&file_name));
+
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .body(content);
+    send(request).await?;
+    Ok(())
+}
+
+// Example usage:
+// let budgets = crate::client::query_budgets("sophie").await?;
+pub async fn query_budgets(username: &str) -> Result<Vec<Budget>, String> {
+    let url = format!("{SERVER_BASE_URL}/query_budgets");
+    let client = new_client();
+    let request = client
         .post(&url)
         .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
-        .body(post_body.clone())
-        .send()
-        .await
-        .unwrap();
-    if !resp.status().is_success() {
-        return Err(String::from("Error: Reqwest failed"));
+        .body(format!("username={}", encode_form_value(username)));
+    let body = send(request).await?;
+    let budgets: Vec<ServerBudgetStatus> = parse(&body)?;
+    Ok(budgets
+        .iter()
+        .map(|b| b.to_budget())
+        .collect::<Vec<Budget>>())
+}
+
+// Example usage:
+// let budget_id_str = crate::client::create_or_update_budget(None, "sophie", "Food", None, 400.0).await;
+pub async fn create_or_update_budget(budget_id: Option<String>,
+                                     username: &str,
+                                     category: &str,
+                                     acct_id: Option<String>,
+                                     amt: f64) -> Result<String, String> {
+    let url = format!("{SERVER_BASE_URL}/create_or_update_budget");
+    let mut post_body = if let Some(bid) = budget_id {
+        format!("budget_id={}&", bid)
+    } else {
+        "".to_string()
+    };
+    if let Some(aid) = acct_id {
+        post_body.push_str(&format!("account_id={}&", aid));
     }
+    post_body.push_str(&format!("username={}&", encode_form_value(username)));
+    post_body.push_str(&format!("category_name={}&", encode_form_value(category)));
+    post_body.push_str(&format!("budget_amount={}", amt));
 
-    Ok(resp.text().await.unwrap())
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(post_body);
+    send(request).await
 }
 
-pub async fn delete_user(username: &str) -> Result<bool, String> {
-    let url = format!("{SERVER_BASE_URL}/delete_user");
-    let client = reqwest::Client::new();
-    let resp = client
+pub async fn delete_budget(budget_id: i64) -> Result<(), String> {
+    let url = format!("{SERVER_BASE_URL}/delete_budget/{budget_id}");
+    let request = new_client().get(&url);
+    send(request).await?;
+    Ok(())
+}
+
+// Example usage:
+// let schedules = crate::client::query_recurring("sophie").await?;
+pub async fn query_recurring(username: &str) -> Result<Vec<RecurringRecord>, String> {
+    let url = format!("{SERVER_BASE_URL}/query_recurring");
+    let client = new_client();
+    let request = client
         .post(&url)
         .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
-        .body(format!("username={}", username))
-        .send()
-        .await;
-    match resp {
-        Ok(response) => Ok(response.status().is_success()),
-        Err(e) => {
-            Err(format!("Error in delete_user: {}", e))
-        }
+        .body(format!("username={}", encode_form_value(username)));
+    let body = send(request).await?;
+    let recurring: Vec<ServerRecurring> = parse(&body)?;
+    Ok(recurring
+        .iter()
+        .map(|r| r.to_recurring())
+        .collect::<Vec<RecurringRecord>>())
+}
+
+// Example usage:
+// let recurring_id_str = crate::client::create_or_update_recurring(&schedule).await;
+pub async fn create_or_update_recurring(recurring: &RecurringRecord) -> Result<String, String> {
+    let url = format!("{SERVER_BASE_URL}/create_or_update_recurring");
+    let mut post_body = if recurring.recurring_id.is_empty() {
+        "".to_string()
+    } else {
+        format!("recurring_id={}&", recurring.recurring_id)
+    };
+    if !recurring.end_date.is_empty() {
+        post_body.push_str(&format!("end_date={}&", recurring.end_date));
     }
+    post_body.push_str(&format!("account_id={}&", recurring.account_id));
+    post_body.push_str(&format!("transaction_type={}&", recurring.trans_type));
+    post_body.push_str(&format!("category={}&", encode_form_value(&recurring.category)));
+    post_body.push_str(&format!("amount={}&", recurring.amount));
+    post_body.push_str(&format!("transaction_memo={}&", encode_form_value(&recurring.description)));
+    post_body.push_str(&format!("frequency={}&", recurring.frequency));
+    post_body.push_str(&format!("start_date={}", recurring.start_date));
+
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(post_body);
+    send(request).await
+}
+
+pub async fn pause_recurring(recurring_id: i64, paused: bool) -> Result<(), String> {
+    let url = format!("{SERVER_BASE_URL}/pause_recurring");
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(format!("recurring_id={recurring_id}&paused={paused}"));
+    send(request).await?;
+    Ok(())
+}
+
+pub async fn delete_recurring(recurring_id: i64) -> Result<(), String> {
+    let url = format!("{SERVER_BASE_URL}/delete_recurring/{recurring_id}");
+    let request = new_client().get(&url);
+    send(request).await?;
+    Ok(())
+}
+
+pub async fn delete_user(username: &str) -> Result<(), String> {
+    let url = format!("{SERVER_BASE_URL}/delete_user");
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(format!("username={}", encode_form_value(username)));
+    send(request).await?;
+    Ok(())
 }
 
-pub async fn delete_account(account_id: i64) -> Result<bool, String> {
+pub async fn delete_account(account_id: i64) -> Result<(), String> {
     let url = format!("{SERVER_BASE_URL}/delete_account/{account_id}");
-    let resp = reqwest::get(&url).await;
-    match resp {
-        Ok(response) => Ok(response.status().is_success()),
-        Err(e) => {
-            Err(format!("Error in delete_account: {}", e))
-        }
-    }
+    let request = new_client().get(&url);
+    send(request).await?;
+    Ok(())
 }
 
-pub async fn delete_transaction(transaction_id: i64) -> Result<bool, String> {
+pub async fn delete_transaction(transaction_id: i64) -> Result<(), String> {
     let url = format!("{SERVER_BASE_URL}/delete_transaction/{transaction_id}");
-    let resp = reqwest::get(&url).await;
-    match resp {
-        Ok(response) => Ok(response.status().is_success()),
-        Err(e) => {
-            Err(format!("Error in delete_transaction: {}", e))
-        }
-    }
+    let request = new_client().get(&url);
+    send(request).await?;
+    Ok(())
 }
 
+// Takes a deleted user, account or transaction back out of the trash
 // Example usage:
-// let (t, s) = client::query_account(1, None, Some("Meal".to_string())).await;
+// crate::client::restore_from_trash("Transaction", 12).await?;
+pub async fn restore_from_trash(entity_type: &str, entity_id: i64) -> Result<(), String> {
+    let url = format!("{SERVER_BASE_URL}/restore_from_trash");
+    let client = new_client();
+    let request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(format!("entity_type={}&entity_id={}", entity_type, entity_id));
+    send(request).await?;
+    Ok(())
+}
+
+// Example usage:
+// let (t, s) = client::query_account(1, None, Some("Meal".to_string()), None, None).await;
 pub async fn query_account(
     account_id: i64,
     trans_type: Option<String>,
     category: Option<String>,
+    tags: Option<String>,
+    tag_match: Option<String>,
 ) -> Result<(Vec<TransRecord>, f64), String> {
     let url = format!("{SERVER_BASE_URL}/query_account");
 
@@ -202,23 +740,22 @@ pub async fn query_account(
         post_body.push_str(&format!("&transaction_type={ttype}"));
     }
     if let Some(tcate) = category {
-        post_body.push_str(&format!("&category={tcate}"));
+        post_body.push_str(&format!("&category={}", encode_form_value(&tcate)));
+    }
+    if let Some(ttags) = tags {
+        post_body.push_str(&format!("&tags={}", encode_form_value(&ttags)));
+    }
+    if let Some(tmatch) = tag_match {
+        post_body.push_str(&format!("&tag_match={tmatch}"));
     }
 
-    let client = reqwest::Client::new();
-    let resp = client
+    let client = new_client();
+    let request = client
         .post(&url)
         .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
-        .body(post_body)
-        .send()
-        .await
-        .unwrap();
-    if !resp.status().is_success() {
-        return Err(String::from("Error: Reqwest failed"));
-    }
-
-    let body = resp.text().await.unwrap();
-    let transactions: (Vec<ServerTransaction>, f64) = serde_json::from_str(&body).unwrap();
+        .body(post_body);
+    let body = send(request).await?;
+    let transactions: (Vec<ServerTransaction>, f64) = parse(&body)?;
     Ok((transactions.0
         .iter()
         .map(|t| t.to_transaction())
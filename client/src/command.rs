@@ -0,0 +1,256 @@
+use chrono::Local;
+
+use crate::app::App;
+use crate::input::{Account, InputContent, ListType, Page, TRANS_SORT_COLUMNS};
+
+// A command of the palette, typed as its name followed by the argument
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub args: &'static str, // shown after the name, empty when there is none
+    pub help: &'static str,
+}
+
+pub const COMMANDS: [CommandSpec; 20] = [
+    CommandSpec { name: "new transaction", args: "", help: "record a transaction on the open account" },
+    CommandSpec { name: "new account", args: "", help: "register a new account" },
+    CommandSpec { name: "switch account", args: "<name>", help: "open the account with that name" },
+    CommandSpec { name: "filter category", args: "<category>", help: "show transactions of a category" },
+    CommandSpec { name: "filter type", args: "<Income|Expenses>", help: "show income or expenses only" },
+    CommandSpec { name: "filter tags", args: "<tag,tag>", help: "show transactions with the tags" },
+    CommandSpec { name: "clear filters", args: "", help: "show all transactions of the account" },
+    CommandSpec { name: "search", args: "<text>", help: "find transactions by memo, category or amount" },
+    CommandSpec { name: "sort", args: "<date|type|category|memo|amount>", help: "sort the transaction table" },
+    CommandSpec { name: "export csv", args: "[path]", help: "save the listed transactions as CSV" },
+    CommandSpec { name: "dashboard", args: "", help: "go to the dashboard" },
+    CommandSpec { name: "accounts", args: "", help: "go to the account details page" },
+    CommandSpec { name: "budgets", args: "", help: "manage monthly budgets" },
+    CommandSpec { name: "recurring", args: "", help: "manage recurring transactions" },
+    CommandSpec { name: "charts", args: "", help: "view spending charts of the account" },
+    CommandSpec { name: "net worth", args: "", help: "view net worth trend" },
+    CommandSpec { name: "undo", args: "", help: "undo the last change" },
+    CommandSpec { name: "redo", args: "", help: "redo the last undone change" },
+    CommandSpec { name: "theme", args: "", help: "switch to the next color theme" },
+    CommandSpec { name: "quit", args: "", help: "exit the application" },
+];
+
+// Most commands kept in the palette history
+pub const COMMAND_HISTORY_LIMIT: usize = 50;
+
+// How well the pattern matches the text when its characters appear in order,
+// higher for matches that are contiguous or start words, and for shorter text
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last: Option<usize> = None;
+    for c in pattern.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = text[pos..].iter().position(|t| *t == c)? + pos;
+        let word_start = found == 0 || text[found - 1] == ' ';
+        score += match last {
+            Some(l) if found == l + 1 => 3,
+            _ if word_start => 3,
+            _ => 1,
+        };
+        last = Some(found);
+        pos = found + 1;
+    }
+    Some(score * 100 - text.len() as i64)
+}
+
+// Commands matching what has been typed, best first, each with the argument
+// typed after it. The leading words can match the command fuzzily with the
+// rest kept as the argument, and once a full command name has been typed
+// only that command is left.
+fn resolve(input: &str) -> Vec<(CommandSpec, String)> {
+    let input = input.trim_start();
+    if input.is_empty() {
+        return COMMANDS.iter().map(|spec| (*spec, String::new())).collect();
+    }
+    if let Some(found) = split_command(input) {
+        return vec![found];
+    }
+    let mut scored: Vec<(i64, CommandSpec, String)> = Vec::new();
+    for spec in COMMANDS.iter() {
+        let mut best = fuzzy_score(input, spec.name).map(|score| (score, String::new()));
+        for (i, _) in input.match_indices(' ') {
+            if let Some(score) = fuzzy_score(&input[..i], spec.name) {
+                if best.as_ref().is_none_or(|(b, _)| score > *b) {
+                    best = Some((score, input[i..].trim().to_string()));
+                }
+            }
+        }
+        if let Some((score, arg)) = best {
+            scored.push((score, *spec, arg));
+        }
+    }
+    scored.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, spec, arg)| (spec, arg)).collect()
+}
+
+pub fn suggestions(input: &str) -> Vec<CommandSpec> {
+    resolve(input).into_iter().map(|(spec, _)| spec).collect()
+}
+
+// The command whose name starts the input, with the rest as its argument
+fn split_command(input: &str) -> Option<(CommandSpec, String)> {
+    let lower = input.to_lowercase();
+    COMMANDS
+        .iter()
+        .filter(|spec| lower == spec.name || lower.starts_with(&format!("{} ", spec.name)))
+        .max_by_key(|spec| spec.name.len())
+        .map(|spec| (*spec, input.get(spec.name.len()..).unwrap_or("").trim().to_string()))
+}
+
+// Completes the input to the best matching command name, keeping the
+// argument typed so far
+pub fn complete(input: &str) -> Option<String> {
+    if split_command(input.trim_start()).is_some() {
+        return None;
+    }
+    resolve(input).into_iter().next().map(|(spec, arg)| {
+        if spec.args.is_empty() {
+            spec.name.to_string()
+        } else {
+            format!("{} {}", spec.name, arg)
+        }
+    })
+}
+
+// Account whose name is the argument, or failing that the closest one
+fn find_account(app: &App, name: &str) -> Option<usize> {
+    let name = name.to_lowercase();
+    let accounts = &app.accounts.items;
+    accounts
+        .iter()
+        .position(|a| a.acct_name.to_lowercase() == name)
+        .or_else(|| accounts.iter().position(|a| a.acct_name.to_lowercase().starts_with(&name)))
+        .or_else(|| {
+            accounts
+                .iter()
+                .enumerate()
+                .filter_map(|(i, a)| fuzzy_score(&name, &a.acct_name).map(|score| (score, i)))
+                .max_by_key(|(score, _)| *score)
+                .map(|(_, i)| i)
+        })
+}
+
+// Runs the command matching the input best. Returns why it could not run.
+pub async fn run_command(app: &mut App, input: &str) -> Result<(), String> {
+    let input = input.trim();
+    let Some((spec, arg)) = resolve(input).into_iter().next() else {
+        return Err(format!("Unknown command: {}", input));
+    };
+    // optional arguments are shown in brackets
+    if arg.is_empty() && spec.args.starts_with('<') {
+        return Err(format!("{} needs {}", spec.name, spec.args));
+    }
+    let account_selected = !app.new_account.acct_id.is_empty();
+    let needs_account = || format!("{}: select an account first", spec.name);
+
+    match spec.name {
+        "new transaction" => {
+            if !account_selected {
+                return Err(needs_account());
+            }
+            app.page = Page::NewTransaction;
+            app.new_trans.timestamp = Local::now().date_naive().to_string();
+            app.input_errors.clear();
+            app.input_content = InputContent::TransactionDescription;
+        },
+        "new account" => {
+            app.page = Page::NewAccount;
+            app.new_account = Account::new("", "", "", "", 0.0);
+            app.input_errors.clear();
+            app.input_content = InputContent::AccountName;
+        },
+        "switch account" => {
+            let idx = find_account(app, &arg).ok_or_else(|| format!("No account matches {}", arg))?;
+            app.open_account(idx).await;
+        },
+        "filter category" | "filter type" | "filter tags" | "clear filters" => {
+            if !account_selected {
+                return Err(needs_account());
+            }
+            let (field, value) = match spec.name {
+                "filter category" => (InputContent::FilterTransCategory, arg.clone()),
+                "filter type" => (InputContent::FilterTransType, arg.clone()),
+                "filter tags" => (InputContent::FilterTransTags, arg.clone()),
+                _ => (InputContent::FilterTransType, String::new()),
+            };
+            field.validate(&value).map_err(|e| format!("{} {}", spec.name, e))?;
+            match field {
+                InputContent::FilterTransCategory => app.filter_trans_category = value,
+                InputContent::FilterTransTags => app.filter_trans_tags = value,
+                _ => app.filter_trans_type = value,
+            }
+            if spec.name == "clear filters" {
+                app.filter_trans_category = "".to_string();
+                app.filter_trans_tags = "".to_string();
+                app.filter_tag_match = "".to_string();
+                app.search_query.clear();
+            }
+            app.page = Page::AccountDetails;
+            app.refresh_transactions().await;
+        },
+        "search" => {
+            if !account_selected {
+                return Err(needs_account());
+            }
+            app.page = Page::AccountDetails;
+            app.update_search(arg);
+        },
+        "sort" => {
+            let column = TRANS_SORT_COLUMNS
+                .iter()
+                .find(|c| c.title().to_lowercase().starts_with(&arg.to_lowercase()))
+                .ok_or_else(|| format!("sort needs one of date, type, category, memo, amount, not {:?}", arg))?;
+            app.page = Page::AccountDetails;
+            app.sort_transactions(*column);
+        },
+        "export csv" => {
+            if !account_selected {
+                return Err(needs_account());
+            }
+            app.export_transactions(&arg)?;
+        },
+        "dashboard" => {
+            app.page = Page::Dashboard;
+            app.refresh_dashboard().await;
+        },
+        "accounts" => {
+            app.list_content = ListType::Acct;
+            app.page = Page::AccountDetails;
+        },
+        "budgets" => {
+            app.page = Page::Budgets;
+            app.clear_budget_form();
+            app.refresh_budgets().await;
+        },
+        "recurring" => {
+            if !account_selected {
+                return Err(needs_account());
+            }
+            app.page = Page::Recurring;
+            app.clear_recurring_form();
+            app.refresh_recurring().await;
+        },
+        "charts" => {
+            if !account_selected {
+                return Err(needs_account());
+            }
+            app.page = Page::Charts;
+            app.refresh_spending_charts().await;
+        },
+        "net worth" => {
+            app.page = Page::NetWorthTrend;
+            app.refresh_net_worth_history().await;
+        },
+        "undo" => app.undo().await,
+        "redo" => app.redo().await,
+        "theme" => app.cycle_theme(),
+        "quit" => app.quit(),
+        _ => {}
+    }
+    Ok(())
+}
@@ -1,218 +1,602 @@
 use crate::app::{App, AppResult};
-use crossterm::event::{KeyCode, KeyEventKind, KeyEvent};
+use chrono::Local;
+use crossterm::event::{KeyCode, KeyEventKind, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use crate::input::{ 
     InputMode, 
     Page, 
     InputContent,
     ListType,
+    ConfirmAction,
+    ClickTarget,
+    Account,
+    TRANS_SORT_COLUMNS,
 };
+use crate::keymap::{Action, CONFIRM_ACTIONS, OPEN_ACCOUNT_ACTIONS, SORT_COLUMN_ACTIONS};
+
+// Actions each page looks up in the keymap, first match wins
+const LOGIN_ACTIONS: [Action; 2] = [Action::Quit, Action::Edit];
+const DASHBOARD_ACTIONS: [Action; 8] = [
+    Action::Quit, Action::SelectList, Action::Add, Action::Budgets, Action::NetWorth, Action::Reload, Action::Back,
+    Action::CycleTheme,
+];
+const ACCOUNT_ACTIONS: [Action; 20] = [
+    Action::Quit, Action::Up, Action::Down, Action::DeleteUser, Action::Home, Action::Edit, Action::Add,
+    Action::NewTransaction, Action::Budgets, Action::Recurring, Action::NetWorth, Action::Charts,
+    Action::SelectList, Action::SelectTransactions, Action::Delete, Action::Undo, Action::Redo, Action::Save,
+    Action::CycleTheme, Action::Search,
+];
+const FORM_ACTIONS: [Action; 7] = [
+    Action::Quit, Action::Up, Action::Down, Action::Edit, Action::Back, Action::Delete, Action::Save,
+];
+const BUDGET_ACTIONS: [Action; 9] = [
+    Action::Quit, Action::Up, Action::Down, Action::Edit, Action::Add, Action::SelectList, Action::Delete,
+    Action::Back, Action::Save,
+];
+const RECURRING_ACTIONS: [Action; 10] = [
+    Action::Quit, Action::Up, Action::Down, Action::Edit, Action::Add, Action::SelectList, Action::Pause,
+    Action::Delete, Action::Back, Action::Save,
+];
+const CHART_ACTIONS: [Action; 3] = [Action::Quit, Action::CyclePeriod, Action::Back];
+const LIST_ACTIONS: [Action; 7] = [
+    Action::Up, Action::Down, Action::PageUp, Action::PageDown, Action::StopSelect, Action::Select, Action::Search,
+];
 
 pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    // the confirmation popup takes every key until it is answered
+    if app.confirmation.is_some() {
+        return handle_confirmation_key_events(key_event, app).await;
+    }
+    // enum values are picked rather than typed
+    if app.input_mode == InputMode::Editing && app.input_content.choices().is_some() {
+        let optional = app.input_content.choices().is_some_and(|choices| choices.first() == Some(&""));
+        if key_event.kind == KeyEventKind::Press {
+            match key_event.code {
+                KeyCode::Enter => app.submit_message().await,
+                KeyCode::Left | KeyCode::Up | KeyCode::BackTab => app.cycle_choice(false),
+                KeyCode::Right | KeyCode::Down | KeyCode::Tab | KeyCode::Char(' ') => app.cycle_choice(true),
+                KeyCode::Char(letter) => app.jump_to_choice(letter),
+                // filters go back to matching everything
+                KeyCode::Backspace if optional => app.pick_choice(0),
+                KeyCode::Esc => app.input_mode = InputMode::Normal,
+                _ => {}
+            }
+        }
+        return Ok(());
+    }
+    // text is typed the same way in every form
+    if app.input_mode == InputMode::Editing {
+        if key_event.kind == KeyEventKind::Press {
+            match key_event.code {
+                KeyCode::Enter => app.submit_message().await,
+                KeyCode::Char(to_insert) => app.enter_char(to_insert),
+                KeyCode::Backspace => app.delete_char(),
+                KeyCode::Left => app.move_cursor_left(),
+                KeyCode::Right => app.move_cursor_right(),
+                // category fields suggest the user's categories
+                KeyCode::Tab => app.accept_suggestion(),
+                KeyCode::Up => app.select_suggestion(false),
+                KeyCode::Down => app.select_suggestion(true),
+                KeyCode::Esc => app.input_mode = InputMode::Normal,
+                _ => {}
+            }
+        }
+        return Ok(());
+    }
+    if app.input_mode == InputMode::ViewAccountList {
+        if key_event.kind == KeyEventKind::Press {
+            handle_list_key_events(key_event, app).await;
+        }
+        return Ok(());
+    }
+    if app.input_mode == InputMode::Command {
+        if key_event.kind == KeyEventKind::Press {
+            handle_command_key_events(key_event, app).await;
+        }
+        return Ok(());
+    }
+    if app.input_mode == InputMode::Search {
+        if key_event.kind == KeyEventKind::Press {
+            handle_search_key_events(key_event, app).await;
+        }
+        return Ok(());
+    }
+    // commands can be typed on every page once logged in
+    if app.page != Page::Login && app.keymap.action_for(key_event.code, &[Action::CommandPalette]).is_some() {
+        app.open_command_palette();
+        return Ok(());
+    }
+
+    let keymap = &app.keymap;
     match app.page {
         Page::Login => {
-            match app.input_mode {
-                InputMode::Normal => match key_event.code {
-                    KeyCode::Esc | KeyCode::Char('q') => {
-                        app.quit();
-                    },
-                    KeyCode::Char('e') => {
-                        // insert username
-                        app.input_content = InputContent::Username;
-                        app.input_mode = InputMode::Editing;
-                    },
-                    _ => {}
-                },
-                InputMode::Editing if key_event.kind == KeyEventKind::Press => match key_event.code {
-                    KeyCode::Enter => app.submit_message().await,
-                    KeyCode::Char(to_insert) => app.enter_char(to_insert),
-                    KeyCode::Backspace => app.delete_char(),
-                    KeyCode::Left => app.move_cursor_left(),
-                    KeyCode::Right => app.move_cursor_right(),
-                    KeyCode::Esc => app.input_mode = InputMode::Normal,
-                    _ => {}
-                },
-                InputMode::Editing => {},
-                InputMode::ViewAccountList => {}
+            match keymap.action_for(key_event.code, &LOGIN_ACTIONS) {
+                Some(Action::Quit) => {
+                    app.quit();
+                },
+                Some(Action::Edit) => {
+                    // insert username
+                    app.input_content = InputContent::Username;
+                    app.start_editing().await;
+                },
+                _ => {}
+            }
+        },
+        Page::Dashboard => {
+            match keymap.action_for(key_event.code, &DASHBOARD_ACTIONS) {
+                Some(Action::Quit) => {
+                    app.quit();
+                },
+                Some(Action::SelectList) => {
+                    // pick an account to open
+                    app.list_content = ListType::Acct;
+                    app.select_first();
+                },
+                Some(Action::Add) => {
+                    // add new account
+                    app.page = Page::NewAccount;
+                    app.new_account = Account::new("", "", "", "", 0.0);
+                    app.input_errors.clear();
+                    app.input_content = InputContent::AccountName;
+                },
+                Some(Action::Budgets) => {
+                    // view monthly budgets
+                    app.page = Page::Budgets;
+                    app.clear_budget_form();
+                    app.refresh_budgets().await;
+                },
+                Some(Action::NetWorth) => {
+                    // view net worth trend
+                    app.page = Page::NetWorthTrend;
+                    app.refresh_net_worth_history().await;
+                },
+                Some(Action::Reload) => app.refresh_dashboard().await,
+                Some(Action::CycleTheme) => app.cycle_theme(),
+                Some(Action::Back) => {
+                    app.page = Page::AccountDetails;
+                },
+                _ => {
+                    // the first nine accounts open with their position
+                    if let Some(Action::OpenAccount(idx)) = keymap.action_for(key_event.code, &OPEN_ACCOUNT_ACTIONS) {
+                        app.open_account(idx).await;
+                    }
+                }
             }
         },
         Page::AccountDetails => {
-            match app.input_mode {
-                InputMode::Normal => match key_event.code {
-                    KeyCode::Esc | KeyCode::Char('q') => {
-                        app.quit();
-                    },
-                    KeyCode::Up => {
-                        if app.new_account.acct_id != "" {
-                            app.prev_input()
-                        }
-                    },
-                    KeyCode::Down => {
-                        if app.new_account.acct_id != "" {
-                            app.next_input()
-                        }
-                    },
-                    KeyCode::Char('b') => {
-                        app.delete_user().await;
-                        app.page = Page::Login;
-                    },
-                    KeyCode::Char('e') => {
-                        if app.new_account.acct_id != "" {
-                            app.input_mode = InputMode::Editing;
-                        }
-                    },
-                    KeyCode::Char('a') => {
-                        // add new account
-                        app.page = Page::NewAccount;
-                        app.new_account.acct_id = "".to_string();
-                        app.new_account.acct_name = "".to_string();
-                        app.new_account.acct_type = "".to_string();
-                        app.new_account.user_id = "".to_string();
-                        app.new_account.card_limit = 0.0;
-                        app.input_content = InputContent::AccountName;
-                    },
-                    KeyCode::Char('t') => {
-                        // add new transaction
-                        app.page = Page::NewTransaction;
-                        app.input_content = InputContent::TransactionDescription;
-                    },
-                    KeyCode::Char('l') => {
-                        // iterate account list
-                        app.list_content = ListType::Acct;
-                        app.select_first();
-                    },
-                    KeyCode::Char('s') => {
-                        // iterate transaction list
-                        if app.new_account.acct_id != "" {
-                            app.list_content = ListType::Trans;
-                            app.select_first();
+            let account_selected = !app.new_account.acct_id.is_empty();
+            match keymap.action_for(key_event.code, &ACCOUNT_ACTIONS) {
+                Some(Action::Quit) => {
+                    app.quit();
+                },
+                Some(Action::Up) if account_selected => app.prev_input(),
+                Some(Action::Down) if account_selected => app.next_input(),
+                Some(Action::DeleteUser) => app.request_confirmation(ConfirmAction::DeleteUser),
+                Some(Action::Home) => {
+                    // back to the dashboard
+                    app.page = Page::Dashboard;
+                    app.refresh_dashboard().await;
+                },
+                Some(Action::Edit) if account_selected => {
+                    app.start_editing().await;
+                },
+                Some(Action::Add) => {
+                    // add new account
+                    app.page = Page::NewAccount;
+                    app.new_account.acct_id = "".to_string();
+                    app.new_account.acct_name = "".to_string();
+                    app.new_account.acct_type = "".to_string();
+                    app.new_account.user_id = "".to_string();
+                    app.new_account.card_limit = 0.0;
+                    app.input_errors.clear();
+                    app.input_content = InputContent::AccountName;
+                },
+                Some(Action::NewTransaction) => {
+                    // add new transaction
+                    app.page = Page::NewTransaction;
+                    app.new_trans.timestamp = Local::now().date_naive().to_string();
+                    app.input_errors.clear();
+                    app.input_content = InputContent::TransactionDescription;
+                },
+                Some(Action::Budgets) => {
+                    // view monthly budgets
+                    app.page = Page::Budgets;
+                    app.clear_budget_form();
+                    app.refresh_budgets().await;
+                },
+                Some(Action::Recurring) if account_selected => {
+                    // manage recurring transactions
+                    app.page = Page::Recurring;
+                    app.clear_recurring_form();
+                    app.refresh_recurring().await;
+                },
+                Some(Action::NetWorth) => {
+                    // view net worth trend
+                    app.page = Page::NetWorthTrend;
+                    app.refresh_net_worth_history().await;
+                },
+                Some(Action::Charts) if account_selected => {
+                    // view spending charts of the account
+                    app.page = Page::Charts;
+                    app.refresh_spending_charts().await;
+                },
+                Some(Action::SelectList) => {
+                    // iterate account list
+                    app.list_content = ListType::Acct;
+                    app.select_first();
+                },
+                Some(Action::SelectTransactions) if account_selected => {
+                    // iterate transaction list
+                    app.list_content = ListType::Trans;
+                    app.select_first();
+                },
+                Some(Action::Delete) => app.request_confirmation(ConfirmAction::DeleteAccount),
+                Some(Action::Undo) => app.undo().await,
+                Some(Action::Redo) => app.redo().await,
+                Some(Action::CycleTheme) => app.cycle_theme(),
+                Some(Action::Search) if account_selected => app.start_search(),
+                Some(Action::Save) => {
+                    app.update_account().await;
+                    app.refresh_transactions().await;
+                },
+                _ => {
+                    // columns are sorted by their position in the table
+                    if let Some(Action::SortColumn(idx)) = keymap.action_for(key_event.code, &SORT_COLUMN_ACTIONS) {
+                        if account_selected {
+                            app.sort_transactions(TRANS_SORT_COLUMNS[idx]);
                         }
-                    },
-                    KeyCode::Char('d') => {
-                        app.delete_account().await;
-                    }
-                    KeyCode::Enter => {
-                        app.update_account().await;
-                        app.refresh_transactions().await;
                     }
-                    _ => {}
-                },
-                InputMode::Editing if key_event.kind == KeyEventKind::Press => match key_event.code {
-                    KeyCode::Enter => app.submit_message().await,
-                    KeyCode::Char(to_insert) => app.enter_char(to_insert),
-                    KeyCode::Backspace => app.delete_char(),
-                    KeyCode::Left => app.move_cursor_left(),
-                    KeyCode::Right => app.move_cursor_right(),
-                    KeyCode::Esc => app.input_mode = InputMode::Normal,
-                    _ => {}
-                },
-                InputMode::Editing => {},
-                InputMode::ViewAccountList if key_event.kind == KeyEventKind::Press => match key_event.code {
-                    KeyCode::Up => app.select_prev(),
-                    KeyCode::Down => app.select_next(),
-                    KeyCode::Esc => {
-                        app.stop_select();
-                        app.input_content = InputContent::AccountName;
-                    },
-                    KeyCode::Enter => {
-                        app.confirm_selection();
-                        if app.list_content == ListType::Acct {
-                            app.filter_trans_category = "".to_string();
-                            app.filter_trans_type = "".to_string();
-                            app.refresh_transactions().await;
-                        }
-                    },
-                    _ => {}
-                },
-                InputMode::ViewAccountList => {}
+                }
             }
         },
         Page::NewAccount => {
-            match app.input_mode {
-                InputMode::Normal => match key_event.code {
-                    KeyCode::Esc | KeyCode::Char('q') => {
-                        app.quit();
-                    },
-                    KeyCode::Up => app.prev_input(),
-                    KeyCode::Down => app.next_input(),
-                    KeyCode::Char('c') => {
-                        app.page = Page::AccountDetails;
-                    },
-                    KeyCode::Char('e') => {
-                        app.input_mode = InputMode::Editing;
-                    },
-                    KeyCode::Enter => {
-                        app.create_new_account().await;
-                        app.page = Page::AccountDetails;
-                    }
-                    _ => {}
+            match keymap.action_for(key_event.code, &FORM_ACTIONS) {
+                Some(Action::Quit) => {
+                    app.quit();
+                },
+                Some(Action::Up) => app.prev_input(),
+                Some(Action::Down) => app.next_input(),
+                Some(Action::Back) => {
+                    app.page = Page::AccountDetails;
                 },
-                InputMode::Editing if key_event.kind == KeyEventKind::Press => match key_event.code {
-                    KeyCode::Enter => app.submit_message().await,
-                    KeyCode::Char(to_insert) => app.enter_char(to_insert),
-                    KeyCode::Backspace => app.delete_char(),
-                    KeyCode::Left => app.move_cursor_left(),
-                    KeyCode::Right => app.move_cursor_right(),
-                    KeyCode::Esc => app.input_mode = InputMode::Normal,
-                    _ => {}
+                Some(Action::Edit) => {
+                    app.start_editing().await;
                 },
-                InputMode::Editing => {},
-                InputMode::ViewAccountList => {}
+                Some(Action::Save) => app.create_new_account().await,
+                _ => {}
             }
         },
         Page::NewTransaction | Page::EditTransaction => {
-            match app.input_mode {
-                InputMode::Normal => match key_event.code {
-                    KeyCode::Esc | KeyCode::Char('q') => {
-                        app.quit();
-                    },
-                    KeyCode::Up => app.prev_input(),
-                    KeyCode::Down => app.next_input(),
-                    KeyCode::Char('e') => {
-                        app.input_mode = InputMode::Editing;
-                    },
-                    KeyCode::Char('c') => {
-                        app.page = Page::AccountDetails;
-                        if app.new_account.acct_id != "" {
-                            app.list_content = ListType::Trans;
-                            app.select_first();
-                        }
-                    },
-                    KeyCode::Char('d') => {
-                        if app.page == Page::EditTransaction {
-                            app.delete_transaction().await;
-                            app.page = Page::AccountDetails;
-                            if app.new_account.acct_id != "" {
-                                app.list_content = ListType::Trans;
-                                app.select_first();
-                            }
-                        }
+            match keymap.action_for(key_event.code, &FORM_ACTIONS) {
+                Some(Action::Quit) => {
+                    app.quit();
+                },
+                Some(Action::Up) => app.prev_input(),
+                Some(Action::Down) => app.next_input(),
+                Some(Action::Edit) => {
+                    app.start_editing().await;
+                },
+                Some(Action::Back) => {
+                    app.page = Page::AccountDetails;
+                    if !app.new_account.acct_id.is_empty() {
+                        app.list_content = ListType::Trans;
+                        app.select_first();
                     }
-                    KeyCode::Enter => {
-                        if app.page == Page::NewTransaction{
-                            app.create_or_update_transaction(true).await;
-                        } else {
-                            app.create_or_update_transaction(false).await;
-                        }
-                        if app.new_account.acct_id != "" {
-                            app.list_content = ListType::Trans;
-                            app.select_first();
-                        }
+                },
+                Some(Action::Delete) if app.page == Page::EditTransaction => {
+                    app.request_confirmation(ConfirmAction::DeleteTransaction);
+                }
+                Some(Action::Save) => {
+                    if app.page == Page::NewTransaction{
+                        app.create_or_update_transaction(true).await;
+                    } else {
+                        app.create_or_update_transaction(false).await;
                     }
-                    _ => {}
+                    // the form stays open when it could not be saved
+                    if app.page == Page::AccountDetails && !app.new_account.acct_id.is_empty() {
+                        app.list_content = ListType::Trans;
+                        app.select_first();
+                    }
+                }
+                _ => {}
+            }
+        },
+        Page::Budgets => {
+            match keymap.action_for(key_event.code, &BUDGET_ACTIONS) {
+                Some(Action::Quit) => {
+                    app.quit();
+                },
+                Some(Action::Up) => app.prev_input(),
+                Some(Action::Down) => app.next_input(),
+                Some(Action::Edit) => {
+                    app.start_editing().await;
+                },
+                Some(Action::Add) => {
+                    // start a new budget
+                    app.clear_budget_form();
+                },
+                Some(Action::SelectList) => {
+                    // iterate budget list
+                    app.list_content = ListType::Budget;
+                    app.select_first();
+                },
+                Some(Action::Delete) => app.request_confirmation(ConfirmAction::DeleteBudget),
+                Some(Action::Back) => {
+                    app.list_content = ListType::Acct;
+                    app.page = Page::AccountDetails;
+                },
+                Some(Action::Save) => {
+                    app.save_budget().await;
+                }
+                _ => {}
+            }
+        },
+        Page::Recurring => {
+            match keymap.action_for(key_event.code, &RECURRING_ACTIONS) {
+                Some(Action::Quit) => {
+                    app.quit();
                 },
-                InputMode::Editing if key_event.kind == KeyEventKind::Press => match key_event.code {
-                    KeyCode::Enter => app.submit_message().await,
-                    KeyCode::Char(to_insert) => app.enter_char(to_insert),
-                    KeyCode::Backspace => app.delete_char(),
-                    KeyCode::Left => app.move_cursor_left(),
-                    KeyCode::Right => app.move_cursor_right(),
-                    KeyCode::Esc => app.input_mode = InputMode::Normal,
-                    _ => {}
+                Some(Action::Up) => app.prev_input(),
+                Some(Action::Down) => app.next_input(),
+                Some(Action::Edit) => {
+                    app.start_editing().await;
                 },
-                InputMode::Editing => {},
-                InputMode::ViewAccountList => {}
+                Some(Action::Add) => {
+                    // start a new schedule
+                    app.clear_recurring_form();
+                },
+                Some(Action::SelectList) => {
+                    // iterate schedule list
+                    app.list_content = ListType::Recurring;
+                    app.select_first();
+                },
+                Some(Action::Pause) => {
+                    app.toggle_recurring_paused().await;
+                },
+                Some(Action::Delete) => app.request_confirmation(ConfirmAction::DeleteRecurring),
+                Some(Action::Back) => {
+                    app.list_content = ListType::Acct;
+                    app.page = Page::AccountDetails;
+                },
+                Some(Action::Save) => {
+                    app.save_recurring().await;
+                }
+                _ => {}
+            }
+        },
+        Page::NetWorthTrend => {
+            match keymap.action_for(key_event.code, &CHART_ACTIONS) {
+                Some(Action::Quit) => {
+                    app.quit();
+                },
+                Some(Action::CyclePeriod) => app.cycle_history_granularity().await,
+                Some(Action::Back) => {
+                    app.page = Page::AccountDetails;
+                },
+                _ => {}
+            }
+        },
+        Page::Charts => {
+            match keymap.action_for(key_event.code, &CHART_ACTIONS) {
+                Some(Action::Quit) => {
+                    app.quit();
+                },
+                Some(Action::CyclePeriod) => app.cycle_chart_range().await,
+                Some(Action::Back) => {
+                    app.page = Page::AccountDetails;
+                },
+                _ => {}
+            }
+        },
+    }
+    Ok(())
+}
+
+// Moving through and picking from the list being selected in
+async fn handle_list_key_events(key_event: KeyEvent, app: &mut App) {
+    let action = app.keymap.action_for(key_event.code, &LIST_ACTIONS);
+    match action {
+        Some(Action::Up) => app.select_prev(),
+        Some(Action::Down) => app.select_next(),
+        Some(Action::PageUp) if app.list_content == ListType::Trans => app.trans_history.state.scroll_up_by(10),
+        Some(Action::PageDown) if app.list_content == ListType::Trans => app.trans_history.state.scroll_down_by(10),
+        Some(Action::StopSelect) => {
+            app.stop_select();
+            if app.page == Page::AccountDetails {
+                app.input_content = InputContent::AccountName;
+            }
+        },
+        Some(Action::Select) => match app.page {
+            Page::Dashboard => {
+                if let Some(idx) = app.accounts.state.selected() {
+                    app.open_account(idx).await;
+                }
+            },
+            Page::AccountDetails => {
+                app.confirm_selection();
+                if app.list_content == ListType::Acct {
+                    app.filter_trans_category = "".to_string();
+                    app.filter_trans_type = "".to_string();
+                    app.filter_trans_tags = "".to_string();
+                    app.filter_tag_match = "".to_string();
+                    app.search_query.clear();
+                    app.refresh_transactions().await;
+                }
+            },
+            Page::Budgets | Page::Recurring => app.confirm_selection(),
+            _ => {}
+        },
+        Some(Action::Search) if app.page == Page::AccountDetails && app.list_content == ListType::Trans => {
+            app.start_search();
+        },
+        _ => match app.keymap.action_for(key_event.code, &SORT_COLUMN_ACTIONS) {
+            Some(Action::SortColumn(idx)) if app.page == Page::AccountDetails && app.list_content == ListType::Trans => {
+                app.sort_transactions(TRANS_SORT_COLUMNS[idx]);
+            },
+            _ => {}
+        }
+    }
+}
+
+// Typing into the command palette
+async fn handle_command_key_events(key_event: KeyEvent, app: &mut App) {
+    match key_event.code {
+        KeyCode::Enter => app.submit_command().await,
+        KeyCode::Esc => app.close_command_palette(),
+        KeyCode::Tab => app.complete_command(),
+        KeyCode::Up => app.recall_command(true),
+        KeyCode::Down => app.recall_command(false),
+        KeyCode::Char(to_insert) => app.command_input.push(to_insert),
+        KeyCode::Backspace => {
+            app.command_input.pop();
+        },
+        _ => {}
+    }
+}
+
+// Typing a search, the transaction list following every key
+async fn handle_search_key_events(key_event: KeyEvent, app: &mut App) {
+    match key_event.code {
+        KeyCode::Enter => {
+            // keep the matches and go through them
+            app.input_mode = InputMode::Normal;
+            if !app.trans_history.items.is_empty() {
+                app.select_first();
+            }
+        },
+        KeyCode::Esc => {
+            app.input_mode = InputMode::Normal;
+            app.update_search(String::new());
+        },
+        KeyCode::Char(to_insert) => {
+            let search = format!("{}{}", app.search_query, to_insert);
+            app.update_search(search);
+        },
+        KeyCode::Backspace => {
+            let mut search = app.search_query.clone();
+            search.pop();
+            app.update_search(search);
+        },
+        _ => {}
+    }
+}
+
+async fn handle_confirmation_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    if key_event.kind != KeyEventKind::Press {
+        return Ok(());
+    }
+    let typed = app.confirmation.as_ref().is_some_and(|c| c.typed_text.is_some());
+    // keys answer the popup only when the confirmation is not typed out
+    let action = if typed { None } else { app.keymap.action_for(key_event.code, &CONFIRM_ACTIONS) };
+    match key_event.code {
+        KeyCode::Esc => app.cancel_confirmation(),
+        _ if action == Some(Action::Cancel) => app.cancel_confirmation(),
+        _ if action == Some(Action::Confirm) => run_confirmed_action(app).await,
+        KeyCode::Enter if app.confirmation_ready() => run_confirmed_action(app).await,
+        KeyCode::Char(to_insert) if typed => app.enter_char(to_insert),
+        KeyCode::Backspace if typed => app.delete_char(),
+        KeyCode::Left if typed => app.move_cursor_left(),
+        KeyCode::Right if typed => app.move_cursor_right(),
+        _ => {}
+    }
+    Ok(())
+}
+
+async fn run_confirmed_action(app: &mut App) {
+    let Some(confirmation) = app.confirmation.take() else {
+        return;
+    };
+    app.cancel_confirmation();
+    match confirmation.action {
+        ConfirmAction::DeleteUser => {
+            app.delete_user().await;
+            // the user stays logged in when the server refused
+            if app.username.is_empty() {
+                app.page = Page::Login;
+            }
+        },
+        ConfirmAction::DeleteAccount => app.delete_account().await,
+        ConfirmAction::DeleteTransaction => {
+            app.delete_transaction().await;
+            app.page = Page::AccountDetails;
+            if !app.new_account.acct_id.is_empty() {
+                app.list_content = ListType::Trans;
+                app.select_first();
+            }
+        },
+        ConfirmAction::DeleteBudget => app.delete_budget().await,
+        ConfirmAction::DeleteRecurring => app.delete_recurring().await,
+    }
+}
+
+// Lists that react to the mouse on the current page, the same ones the keys
+// can select
+fn list_clickable(app: &App, list: &ListType) -> bool {
+    match app.page {
+        Page::Dashboard => *list == ListType::Acct,
+        Page::AccountDetails => *list == ListType::Acct || (*list == ListType::Trans && !app.new_account.acct_id.is_empty()),
+        Page::Budgets => *list == ListType::Budget,
+        Page::Recurring => *list == ListType::Recurring,
+        _ => false,
+    }
+}
+
+async fn press_key(code: KeyCode, app: &mut App) -> AppResult<()> {
+    handle_key_events(KeyEvent::new(code, KeyModifiers::NONE), app).await
+}
+
+pub async fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<()> {
+    let Some(target) = app.target_at(mouse_event.column, mouse_event.row) else {
+        return Ok(());
+    };
+    // only the buttons answer the confirmation popup and the command palette
+    let modal = app.confirmation.is_some() || app.input_mode == InputMode::Command;
+    if modal && !matches!(target, ClickTarget::Button(_)) {
+        return Ok(());
+    }
+    match (mouse_event.kind, target) {
+        (MouseEventKind::Down(MouseButton::Left), ClickTarget::Button(code)) => press_key(code, app).await?,
+        (MouseEventKind::Down(MouseButton::Left), ClickTarget::Item(list, idx)) if list_clickable(app, &list) => {
+            let selected = match list {
+                ListType::Acct => app.accounts.state.selected(),
+                ListType::Trans => app.trans_history.state.selected(),
+                ListType::Budget => app.budgets.state.selected(),
+                ListType::Recurring => app.recurring.state.selected(),
+            };
+            // a second click on the selected row opens it
+            if app.input_mode == InputMode::ViewAccountList && app.list_content == list && selected == Some(idx) {
+                if let Some(code) = app.keymap.keys(Action::Select).first() {
+                    press_key(*code, app).await?;
+                }
+            } else {
+                app.input.clear();
+                app.list_content = list;
+                app.select_at(idx);
+            }
+        },
+        (MouseEventKind::Down(MouseButton::Left), ClickTarget::Choice(_, idx)) => {
+            app.pick_choice(idx);
+            app.submit_message().await;
+        },
+        (MouseEventKind::Down(MouseButton::Left), ClickTarget::Field(content)) => {
+            if app.input_mode == InputMode::ViewAccountList {
+                app.stop_select();
+            }
+            app.input.clear();
+            app.character_index = 0;
+            app.input_content = content;
+            app.start_editing().await;
+        },
+        (MouseEventKind::ScrollDown, ClickTarget::List(list) | ClickTarget::Item(list, _)) if list_clickable(app, &list) => {
+            if app.input_mode == InputMode::ViewAccountList && app.list_content == list {
+                app.select_next();
+            } else {
+                app.list_content = list;
+                app.select_first();
             }
         },
+        (MouseEventKind::ScrollUp, ClickTarget::List(list) | ClickTarget::Item(list, _))
+            if app.input_mode == InputMode::ViewAccountList && app.list_content == list => app.select_prev(),
+        _ => {}
     }
     Ok(())
 }
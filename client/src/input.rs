@@ -1,37 +1,37 @@
+use chrono::{Datelike, Days, Local, NaiveDate, Weekday};
+use crossterm::event::KeyCode;
 use ratatui::{
-    style::{
-        palette::tailwind::SLATE,
-        Color, Modifier, Style,
-    },
-    text::Line,
-    widgets::{ListItem, ListState},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Cell, ListItem, ListState, Row, TableState},
 };
-
-pub const TODO_HEADER_STYLE: Style = Style::new().fg(SLATE.c100).bg(Color::Cyan);
-pub const NORMAL_ROW_BG: Color = Color::Black;
-pub const ALT_ROW_BG_COLOR: Color = Color::Black;
-pub const SELECTED_STYLE: Style = Style::new().bg(Color::Yellow).add_modifier(Modifier::BOLD);
-pub const TEXT_FG_COLOR: Color = Color::Cyan;
-pub const COMPLETED_TEXT_FG_COLOR: Color = Color::Cyan;
+use crate::theme::Theme;
 
 
 #[derive(Debug, PartialEq)]
 pub enum InputMode {
     Normal,
     Editing,
-    ViewAccountList
+    ViewAccountList,
+    Command,
+    Search,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Page {
     Login,
+    Dashboard,
     AccountDetails,
     NewAccount,
     NewTransaction,
     EditTransaction,
+    NetWorthTrend,
+    Budgets,
+    Recurring,
+    Charts,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum InputContent {
     Username,
     AccountID,
@@ -42,16 +42,70 @@ pub enum InputContent {
     TransactionAmount,
     TransactionCategory,
     TransactionDescription,
+    TransactionDate,
+    TransactionTags,
+    TransactionSplits,
+    TransactionAttachment,
     FilterTransType,
     FilterTransCategory,
+    FilterTransTags,
+    FilterTagMatch,
+    BudgetCategory,
+    BudgetAmount,
+    BudgetAccount,
+    RecurringDescription,
+    RecurringType,
+    RecurringAmount,
+    RecurringCategory,
+    RecurringFrequency,
+    RecurringStartDate,
+    RecurringEndDate,
 }
 
+pub const HISTORY_GRANULARITIES: [&str; 3] = ["Daily", "Weekly", "Monthly"];
+pub const CHART_RANGES: [&str; 4] = ["3 Months", "6 Months", "12 Months", "All Time"];
+pub const RECURRING_FREQUENCIES: [&str; 4] = ["Daily", "Weekly", "Monthly", "Yearly"];
+pub const ACCOUNT_TYPES: [&str; 3] = ["Chequing", "Savings", "Credit"];
+pub const TRANSACTION_TYPES: [&str; 2] = ["Income", "Expenses"];
+pub const TAG_MATCHES: [&str; 2] = ["Any", "All"];
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum ListType {
     Acct,
     Trans,
+    Budget,
+    Recurring,
 }
 
+// What is under the mouse, recorded for each region while rendering
+#[derive(Debug, PartialEq, Clone)]
+pub enum ClickTarget {
+    List(ListType), // the whole list, scrolled with the wheel
+    Item(ListType, usize), // one row of a list
+    Field(InputContent),
+    Choice(InputContent, usize), // one value of a selector being edited
+    Button(KeyCode), // acts as if the key was pressed
+}
+
+// Column of the transaction table the rows are ordered by
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TransSortColumn {
+    Date,
+    Type,
+    Category,
+    Memo,
+    Amount,
+}
+
+// Sortable columns in table order, picked with the keys 1 to 5
+pub const TRANS_SORT_COLUMNS: [TransSortColumn; 5] = [
+    TransSortColumn::Date,
+    TransSortColumn::Type,
+    TransSortColumn::Category,
+    TransSortColumn::Memo,
+    TransSortColumn::Amount,
+];
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct TransRecord {
     pub transaction_id: String,
@@ -60,20 +114,162 @@ pub struct TransRecord {
     pub category: String,
     pub description: String,
     pub amount: f64,
+    pub tags: Vec<String>,
+    pub splits: Vec<SplitRecord>,
+    pub attachment_count: i64,
+    pub balance: f64, // account balance after it over the whole history, as computed by the server
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SplitRecord {
+    pub category: String,
+    pub amount: f64,
+    pub memo: String,
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Account {
     pub acct_id: String, 
     pub acct_name: String,
-    pub acct_type: String, // Credit or Chequing or Savings?
+    pub acct_type: String, // Credit or Chequing or Savings
     pub user_id: String,
     pub card_limit: f64,
+    pub balance: f64,
+    pub transaction_count: i64,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct NetWorth {
+    pub total_assets: f64,
+    pub total_liabilities: f64, // balances owed on credit accounts
+    pub net_worth: f64,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct Budget {
+    pub budget_id: String,
+    pub category: String,
+    pub account_id: String, // empty when the budget covers all accounts
+    pub budgeted: f64,
+    pub actual: f64,
+    pub remaining: f64,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct RecurringRecord {
+    pub recurring_id: String,
+    pub account_id: String,
+    pub account_name: String,
+    pub trans_type: String, // expense or income
+    pub category: String,
+    pub description: String,
+    pub amount: f64,
+    pub frequency: String, // Daily, Weekly, Monthly or Yearly
+    pub start_date: String,
+    pub end_date: String, // empty when the schedule never ends
+    pub next_date: String,
+    pub paused: bool,
+}
+
+// Server-side aggregates of the selected account for the charts page
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SpendingCharts {
+    pub by_category: Vec<(String, f64)>, // amount spent, largest first
+    pub monthly: Vec<(String, f64, f64)>, // month, income, expenses
+    pub balance: Vec<(String, f64)>,
+}
+
+// Credit account with how much of its limit is owed
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CreditUtilization {
+    pub acct_id: String,
+    pub acct_name: String,
+    pub owed: f64,
+    pub card_limit: f64,
+    pub utilization: f64, // 0 when the account has no limit
+}
+
+// Home page aggregates of all the user's accounts for the current month
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Dashboard {
+    pub month: String,
+    pub income: f64,
+    pub expenses: f64,
+    pub top_categories: Vec<(String, f64)>, // amount spent, largest first
+    pub upcoming_recurring: Vec<RecurringRecord>,
+    pub credit_utilization: Vec<CreditUtilization>,
+}
+
+// A category of the user with how much it has been used, as ranked by the
+// server
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CategoryUsage {
+    pub name: String,
+    pub use_count: i64,
+    pub last_used: String, // empty when never used
+}
+
+// Most suggestions listed under a category field
+pub const CATEGORY_SUGGESTIONS: usize = 5;
+
+// Categories fitting what has been typed, the ones starting with it before
+// the ones only containing it, each kept in the server's ranking
+pub fn suggest_categories<'a>(categories: &'a [CategoryUsage], typed: &str) -> Vec<&'a CategoryUsage> {
+    let typed = typed.trim().to_lowercase();
+    let (mut starting, containing): (Vec<&CategoryUsage>, Vec<&CategoryUsage>) = categories
+        .iter()
+        .filter(|c| c.name.to_lowercase().contains(&typed))
+        .partition(|c| c.name.to_lowercase().starts_with(&typed));
+    starting.extend(containing);
+    starting.truncate(CATEGORY_SUGGESTIONS);
+    starting
+}
+
+// Outcome of the last server call, shown in the status bar
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct StatusMessage {
+    pub time: String,
+    pub text: String,
+    pub is_error: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ConfirmAction {
+    DeleteUser,
+    DeleteAccount,
+    DeleteTransaction,
+    DeleteBudget,
+    DeleteRecurring,
+}
+
+// A destructive action waiting for the user to confirm it in a popup
+#[derive(Debug, PartialEq, Clone)]
+pub struct Confirmation {
+    pub action: ConfirmAction,
+    pub title: String,
+    pub details: Vec<String>, // what will be deleted
+    pub typed_text: Option<String>, // must be typed out when pressing y is not enough
+}
+
+// A change made from the TUI, holding what is needed to reverse and replay it
+#[derive(Debug, PartialEq, Clone)]
+pub enum UndoAction {
+    CreateAccount(Account),
+    UpdateAccount { before: Account, after: Account },
+    DeleteAccount(Account),
+    CreateTransaction { account_id: String, record: TransRecord },
+    UpdateTransaction { account_id: String, before: TransRecord, after: TransRecord },
+    DeleteTransaction { account_id: String, record: TransRecord },
+}
+
+// Most actions kept on the undo stack
+pub const UNDO_LIMIT: usize = 50;
+
 pub struct TransList {
     pub items: Vec<TransRecord>,
-    pub state: ListState,
+    pub state: TableState,
+    pub sort_column: TransSortColumn,
+    pub sort_descending: bool,
 }
 
 pub struct AccountList {
@@ -81,6 +277,16 @@ pub struct AccountList {
     pub state: ListState,
 }
 
+pub struct BudgetList {
+    pub items: Vec<Budget>,
+    pub state: ListState,
+}
+
+pub struct RecurringList {
+    pub items: Vec<RecurringRecord>,
+    pub state: ListState,
+}
+
 impl TransRecord {
     pub fn new(trans_id: &str, timestamp: &str, trans_type: &str, category: &str, descrip: &str, amt: f64) -> Self {
         Self {
@@ -90,11 +296,200 @@ impl TransRecord {
             category: category.to_string(),
             description: descrip.to_string(),
             amount: amt,
+            tags: Vec::new(),
+            splits: Vec::new(),
+            attachment_count: 0,
+            balance: 0.0,
+        }
+    }
+}
 
+fn require_one_of(value: &str, options: &[&str]) -> Result<(), String> {
+    if options.contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("must be one of {}", options.join(", ")))
+    }
+}
+
+fn require_number(value: &str) -> Result<f64, String> {
+    value.trim().parse::<f64>().map_err(|_| "must be a number".to_string())
+}
+
+fn require_iso_date(value: &str) -> Result<(), String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| "must be a date as YYYY-MM-DD".to_string())
+}
+
+impl InputContent {
+    // Values of the fields taking one of the server's enum values, picked
+    // with a selector rather than typed. Filters can also be left empty.
+    pub fn choices(&self) -> Option<Vec<&'static str>> {
+        let (options, optional): (&[&'static str], bool) = match self {
+            InputContent::AccountType => (&ACCOUNT_TYPES, false),
+            InputContent::TransactionType | InputContent::RecurringType => (&TRANSACTION_TYPES, false),
+            InputContent::RecurringFrequency => (&RECURRING_FREQUENCIES, false),
+            InputContent::FilterTransType => (&TRANSACTION_TYPES, true),
+            InputContent::FilterTagMatch => (&TAG_MATCHES, true),
+            _ => return None,
+        };
+        let mut choices = if optional { vec![""] } else { Vec::new() };
+        choices.extend_from_slice(options);
+        Some(choices)
+    }
+
+    // Fields taking one of the user's categories, which suggest them
+    pub fn is_category(&self) -> bool {
+        matches!(
+            self,
+            InputContent::TransactionCategory
+                | InputContent::FilterTransCategory
+                | InputContent::BudgetCategory
+                | InputContent::RecurringCategory
+        )
+    }
+
+    // Checks a value typed into the field, returning the message to show
+    // under the field when it is not acceptable
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            InputContent::Username | InputContent::AccountName | InputContent::BudgetCategory => {
+                if value.trim().is_empty() {
+                    Err("must not be empty".to_string())
+                } else {
+                    Ok(())
+                }
+            },
+            InputContent::AccountID | InputContent::BudgetAccount => {
+                if value.is_empty() || value.trim().parse::<i64>().is_ok() {
+                    Ok(())
+                } else {
+                    Err("must be an account id".to_string())
+                }
+            },
+            InputContent::AccountType => require_one_of(value, &ACCOUNT_TYPES),
+            InputContent::AccountLimit => {
+                if require_number(value)? < 0.0 {
+                    Err("must not be negative".to_string())
+                } else {
+                    Ok(())
+                }
+            },
+            InputContent::TransactionType | InputContent::RecurringType => require_one_of(value, &TRANSACTION_TYPES),
+            // expenses loaded from the server are negative
+            InputContent::TransactionAmount | InputContent::RecurringAmount => {
+                if require_number(value)? == 0.0 {
+                    Err("must not be zero".to_string())
+                } else {
+                    Ok(())
+                }
+            },
+            InputContent::BudgetAmount => {
+                if require_number(value)? <= 0.0 {
+                    Err("must be more than zero".to_string())
+                } else {
+                    Ok(())
+                }
+            },
+            InputContent::TransactionDate => {
+                parse_date_input(value, Local::now().date_naive())
+                    .map(|_| ())
+                    .ok_or_else(|| "must be YYYY-MM-DD, today, yesterday, -3d or a weekday".to_string())
+            },
+            InputContent::TransactionSplits => {
+                for line in value.split(';').filter(|line| !line.trim().is_empty()) {
+                    let mut parts = line.splitn(3, ':');
+                    let category = parts.next().unwrap_or("").trim();
+                    let amount = parts.next().unwrap_or("");
+                    if category.is_empty() || require_number(amount).is_err() {
+                        return Err(format!("{:?} is not category:amount[:memo]", line.trim()));
+                    }
+                }
+                Ok(())
+            },
+            InputContent::TransactionAttachment => {
+                if value.trim().is_empty() || std::path::Path::new(value.trim()).is_file() {
+                    Ok(())
+                } else {
+                    Err("no such file".to_string())
+                }
+            },
+            InputContent::FilterTransType => {
+                if value.is_empty() { Ok(()) } else { require_one_of(value, &TRANSACTION_TYPES) }
+            },
+            InputContent::FilterTagMatch => {
+                if value.is_empty() { Ok(()) } else { require_one_of(value, &TAG_MATCHES) }
+            },
+            InputContent::RecurringFrequency => require_one_of(value, &RECURRING_FREQUENCIES),
+            InputContent::RecurringStartDate => require_iso_date(value),
+            InputContent::RecurringEndDate => {
+                if value.is_empty() { Ok(()) } else { require_iso_date(value) }
+            },
+            InputContent::TransactionCategory
+            | InputContent::TransactionDescription
+            | InputContent::TransactionTags
+            | InputContent::FilterTransCategory
+            | InputContent::FilterTransTags
+            | InputContent::RecurringDescription
+            | InputContent::RecurringCategory => Ok(()),
         }
     }
 }
 
+// Parses a date typed by the user relative to today: an ISO date, "today",
+// "yesterday", a number of days back such as "-3d", or a weekday such as "mon"
+// meaning its most recent occurrence
+pub fn parse_date_input(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase();
+    match input.as_str() {
+        "today" => return Some(today),
+        "yesterday" => return today.checked_sub_days(Days::new(1)),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+        return Some(date);
+    }
+    if let Some(days) = input.strip_prefix('-').and_then(|i| i.strip_suffix('d')) {
+        return today.checked_sub_days(Days::new(days.parse().ok()?));
+    }
+    let weekday = input.parse::<Weekday>().ok()?;
+    let days_back = (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    today.checked_sub_days(Days::new(days_back as u64))
+}
+
+impl SplitRecord {
+    // Parses "category:amount[:memo]" lines separated by semicolons
+    pub fn parse_list(input: &str) -> Vec<SplitRecord> {
+        input
+            .split(';')
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut parts = line.splitn(3, ':');
+                SplitRecord {
+                    category: parts.next().unwrap_or("").trim().to_string(),
+                    amount: parts.next().unwrap_or("").trim().parse::<f64>().unwrap_or(0.0),
+                    memo: parts.next().unwrap_or("").trim().to_string(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn format_list(splits: &[SplitRecord]) -> String {
+        splits
+            .iter()
+            .map(|split| {
+                if split.memo.is_empty() {
+                    format!("{}:{}", split.category, split.amount)
+                } else {
+                    format!("{}:{}:{}", split.category, split.amount, split.memo)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("; ")
+    }
+}
+
 impl Account {
     pub fn new(acct_id: &str, account_name: &str, user_id: &str, acct_type: &str, card_limit: f64) -> Self {
         Self {
@@ -102,19 +497,124 @@ impl Account {
             acct_name: account_name.to_string(),
             acct_type: acct_type.to_string(),
             user_id: user_id.to_string(),
-            card_limit: card_limit,
+            card_limit,
+            balance: 0.0,
+            transaction_count: 0,
+        }
+    }
+}
+
+impl UndoAction {
+    pub fn describe(&self) -> String {
+        match self {
+            UndoAction::CreateAccount(account) => format!("creating account {}", account.acct_name),
+            UndoAction::UpdateAccount { after, .. } => format!("editing account {}", after.acct_name),
+            UndoAction::DeleteAccount(account) => format!("deleting account {}", account.acct_name),
+            UndoAction::CreateTransaction { record, .. } => format!("creating transaction {}", record.description),
+            UndoAction::UpdateTransaction { after, .. } => format!("editing transaction {}", after.description),
+            UndoAction::DeleteTransaction { record, .. } => format!("deleting transaction {}", record.description),
+        }
+    }
+}
+
+impl Budget {
+    pub fn new(budget_id: &str, category: &str, account_id: &str, budgeted: f64) -> Self {
+        Self {
+            budget_id: budget_id.to_string(),
+            category: category.to_string(),
+            account_id: account_id.to_string(),
+            budgeted,
+            actual: 0.0,
+            remaining: budgeted,
         }
     }
 }
 
+impl FromIterator<Budget> for BudgetList {
+    fn from_iter<I: IntoIterator<Item = Budget>>(iter: I) -> Self {
+        let items = iter.into_iter().collect();
+        let state = ListState::default();
+        Self { items, state }
+    }
+}
+
+impl FromIterator<RecurringRecord> for RecurringList {
+    fn from_iter<I: IntoIterator<Item = RecurringRecord>>(iter: I) -> Self {
+        let items = iter.into_iter().collect();
+        let state = ListState::default();
+        Self { items, state }
+    }
+}
+
 impl FromIterator<(&'static str, &'static str, &'static str, &'static str, &'static str, f64)> for TransList {
     fn from_iter<I: IntoIterator<Item = (&'static str, &'static str, &'static str, &'static str, &'static str, f64)>>(iter: I) -> Self {
         let items = iter
             .into_iter()
             .map(|(trans_id, timestamp, trans_type, category, descrip, amt)| TransRecord::new(trans_id, timestamp, trans_type, category, descrip, amt))
             .collect();
-        let state = ListState::default();
-        Self { items, state }
+        let mut list = Self {
+            items,
+            state: TableState::default(),
+            sort_column: TransSortColumn::Date,
+            sort_descending: true,
+        };
+        list.sort();
+        list
+    }
+}
+
+impl TransSortColumn {
+    pub fn title(&self) -> &'static str {
+        match self {
+            TransSortColumn::Date => "Date",
+            TransSortColumn::Type => "Type",
+            TransSortColumn::Category => "Category",
+            TransSortColumn::Memo => "Memo",
+            TransSortColumn::Amount => "Amount",
+        }
+    }
+}
+
+impl TransList {
+    // Orders the items by the sort column, ties broken by date and id
+    pub fn sort(&mut self) {
+        let chronological = |a: &TransRecord, b: &TransRecord| {
+            a.timestamp.cmp(&b.timestamp).then_with(|| {
+                let a_id = a.transaction_id.parse::<i64>().unwrap_or(0);
+                let b_id = b.transaction_id.parse::<i64>().unwrap_or(0);
+                a_id.cmp(&b_id)
+            })
+        };
+
+        let column = self.sort_column;
+        self.items.sort_by(|a, b| {
+            let order = match column {
+                TransSortColumn::Date => std::cmp::Ordering::Equal,
+                TransSortColumn::Type => a.trans_type.cmp(&b.trans_type),
+                TransSortColumn::Category => a.category.to_lowercase().cmp(&b.category.to_lowercase()),
+                TransSortColumn::Memo => a.description.to_lowercase().cmp(&b.description.to_lowercase()),
+                TransSortColumn::Amount => a.amount.total_cmp(&b.amount),
+            };
+            order.then_with(|| chronological(a, b))
+        });
+        if self.sort_descending {
+            self.items.reverse();
+        }
+    }
+
+    // Sorts by the column, or flips the direction when it is already sorted by it
+    pub fn sort_by(&mut self, column: TransSortColumn) {
+        if self.sort_column == column {
+            self.sort_descending = !self.sort_descending;
+        } else {
+            self.sort_column = column;
+            self.sort_descending = false;
+        }
+        self.sort();
+    }
+
+    pub fn selected_item(&self) -> Option<&TransRecord> {
+        self.items.get(self.state.selected()?)
     }
 }
 
@@ -129,16 +629,78 @@ impl FromIterator<(&'static str, &'static str, &'static str, &'static str, f64)>
     }
 }
 
-impl From<&Account> for ListItem<'_> {
-    fn from(value: &Account) -> Self {
-        let line = Line::styled(format!(" - {}: {}, {}", value.acct_id, value.acct_name, value.acct_type), COMPLETED_TEXT_FG_COLOR);
+impl Account {
+    // List entry with the balance colored by its sign
+    pub fn to_list_item(&self, theme: &Theme) -> ListItem<'_> {
+        let line = Line::from(vec![
+            Span::styled(format!(" - {}: {}, {} (", self.acct_id, self.acct_name, self.acct_type), Style::new().fg(theme.list_fg)),
+            Span::styled(format!("{:.2}", self.balance), Style::new().fg(theme.amount(self.balance))),
+            Span::styled(")", Style::new().fg(theme.list_fg)),
+        ]);
         ListItem::new(line)
     }
 }
 
-impl From<&TransRecord> for ListItem<'_> {
-    fn from(value: &TransRecord) -> Self {
-        let line = Line::styled(format!(" - {}: {}, {}", value.transaction_id, value.trans_type, value.amount), COMPLETED_TEXT_FG_COLOR);
+impl TransRecord {
+    // Row of the transaction table, with the account balance after it and
+    // the text matching the search highlighted
+    pub fn to_row(&self, theme: &Theme, search: &str) -> Row<'_> {
+        let memo = if self.splits.is_empty() {
+            self.description.clone()
+        } else {
+            format!("{} ({} splits)", self.description, self.splits.len())
+        };
+        let matched = theme.matched();
+        Row::new(vec![
+            Cell::from(self.timestamp.clone()),
+            Cell::from(self.trans_type.clone()),
+            Cell::from(highlight(self.category.clone(), search, matched)),
+            Cell::from(highlight(memo, search, matched)),
+            Cell::from(highlight(format!("{:.2}", self.amount), search, matched).right_aligned()).style(Style::new().fg(theme.amount(self.amount))),
+            Cell::from(Line::from(format!("{:.2}", self.balance)).right_aligned()),
+        ])
+        .style(Style::new().fg(theme.list_fg))
+    }
+
+    // Whether the search text is in the memo, the category of the
+    // transaction or of a split, or the amount, ignoring case. Like the
+    // server, a memo containing every word of the search matches too.
+    pub fn matches(&self, search: &str) -> bool {
+        let search = search.trim().to_lowercase();
+        let memo = self.description.to_lowercase();
+        let contains = |text: &str| text.to_lowercase().contains(&search);
+        contains(&self.description)
+            || contains(&self.category)
+            || format!("{:.2}", self.amount.abs()).contains(&search)
+            || self.splits.iter().any(|split| contains(&split.category))
+            || search.split_whitespace().all(|word| memo.contains(word))
+    }
+}
+
+// Line of the text with the first occurrence of the search, ignoring case,
+// drawn in the given style
+fn highlight<'a>(text: String, search: &str, style: Style) -> Line<'a> {
+    let search = search.trim().to_lowercase();
+    let lower = text.to_lowercase();
+    let found = if search.is_empty() || lower.len() != text.len() { None } else { lower.find(&search) };
+    match found {
+        Some(start) if text.is_char_boundary(start) && text.is_char_boundary(start + search.len()) => {
+            let end = start + search.len();
+            Line::from(vec![
+                Span::raw(text[..start].to_string()),
+                Span::styled(text[start..end].to_string(), style),
+                Span::raw(text[end..].to_string()),
+            ])
+        },
+        _ => Line::from(text),
+    }
+}
+
+impl RecurringRecord {
+    // List entry colored by whether the schedule brings money in or out
+    pub fn to_list_item(&self, theme: &Theme) -> ListItem<'_> {
+        let status = if self.paused { "paused".to_string() } else { format!("next {}", self.next_date) };
+        let line = Line::styled(format!(" - {}: {} {} {}, {} on {} ({})", self.recurring_id, self.frequency, self.trans_type, self.amount, self.description, self.account_name, status), theme.trans_type(&self.trans_type));
         ListItem::new(line)
     }
-}
\ No newline at end of file
+}
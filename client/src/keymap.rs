@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crossterm::event::KeyCode;
+
+// Something a key can do. Pages only look at their own actions, so the same
+// key may be bound to actions that never show up on the same page.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Action {
+    Quit,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Edit,
+    Save,
+    Back,
+    Home,
+    Add,
+    NewTransaction,
+    Budgets,
+    Recurring,
+    NetWorth,
+    Charts,
+    SelectList,
+    SelectTransactions,
+    Select,
+    StopSelect,
+    Delete,
+    DeleteUser,
+    Undo,
+    Redo,
+    Pause,
+    CyclePeriod,
+    Reload,
+    CycleTheme,
+    CommandPalette,
+    Search,
+    OpenAccount(usize), // position of the account on the dashboard, from 0
+    SortColumn(usize),  // position of the column in the transaction table, from 0
+    Confirm,
+    Cancel,
+}
+
+pub const OPEN_ACCOUNT_ACTIONS: [Action; 9] = [
+    Action::OpenAccount(0),
+    Action::OpenAccount(1),
+    Action::OpenAccount(2),
+    Action::OpenAccount(3),
+    Action::OpenAccount(4),
+    Action::OpenAccount(5),
+    Action::OpenAccount(6),
+    Action::OpenAccount(7),
+    Action::OpenAccount(8),
+];
+pub const SORT_COLUMN_ACTIONS: [Action; 5] = [
+    Action::SortColumn(0),
+    Action::SortColumn(1),
+    Action::SortColumn(2),
+    Action::SortColumn(3),
+    Action::SortColumn(4),
+];
+pub const CONFIRM_ACTIONS: [Action; 2] = [Action::Confirm, Action::Cancel];
+
+const OPEN_ACCOUNT_NAMES: [&str; 9] = [
+    "open_account_1", "open_account_2", "open_account_3", "open_account_4", "open_account_5",
+    "open_account_6", "open_account_7", "open_account_8", "open_account_9",
+];
+const SORT_COLUMN_NAMES: [&str; 5] = ["sort_column_1", "sort_column_2", "sort_column_3", "sort_column_4", "sort_column_5"];
+
+pub const ACTIONS: [Action; 45] = [
+    Action::Quit,
+    Action::Up,
+    Action::Down,
+    Action::PageUp,
+    Action::PageDown,
+    Action::Edit,
+    Action::Save,
+    Action::Back,
+    Action::Home,
+    Action::Add,
+    Action::NewTransaction,
+    Action::Budgets,
+    Action::Recurring,
+    Action::NetWorth,
+    Action::Charts,
+    Action::SelectList,
+    Action::SelectTransactions,
+    Action::Select,
+    Action::StopSelect,
+    Action::Delete,
+    Action::DeleteUser,
+    Action::Undo,
+    Action::Redo,
+    Action::Pause,
+    Action::CyclePeriod,
+    Action::Reload,
+    Action::CycleTheme,
+    Action::CommandPalette,
+    Action::Search,
+    Action::OpenAccount(0),
+    Action::OpenAccount(1),
+    Action::OpenAccount(2),
+    Action::OpenAccount(3),
+    Action::OpenAccount(4),
+    Action::OpenAccount(5),
+    Action::OpenAccount(6),
+    Action::OpenAccount(7),
+    Action::OpenAccount(8),
+    Action::SortColumn(0),
+    Action::SortColumn(1),
+    Action::SortColumn(2),
+    Action::SortColumn(3),
+    Action::SortColumn(4),
+    Action::Confirm,
+    Action::Cancel,
+];
+
+pub const KEYMAP_PRESETS: [&str; 2] = ["default", "vim"];
+
+// File read at startup, unless FINANCE_TRACKER_KEYMAP points somewhere else
+const KEYMAP_FILE: &str = ".config/finance-tracker/keymap.conf";
+
+impl Action {
+    // Name of the action in the keymap file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::Edit => "edit",
+            Action::Save => "save",
+            Action::Back => "back",
+            Action::Home => "home",
+            Action::Add => "add",
+            Action::NewTransaction => "new_transaction",
+            Action::Budgets => "budgets",
+            Action::Recurring => "recurring",
+            Action::NetWorth => "net_worth",
+            Action::Charts => "charts",
+            Action::SelectList => "select_list",
+            Action::SelectTransactions => "select_transactions",
+            Action::Select => "select",
+            Action::StopSelect => "stop_select",
+            Action::Delete => "delete",
+            Action::DeleteUser => "delete_user",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Pause => "pause",
+            Action::CyclePeriod => "cycle_period",
+            Action::Reload => "reload",
+            Action::CycleTheme => "cycle_theme",
+            Action::CommandPalette => "command_palette",
+            Action::Search => "search",
+            Action::OpenAccount(idx) => OPEN_ACCOUNT_NAMES[*idx],
+            Action::SortColumn(idx) => SORT_COLUMN_NAMES[*idx],
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = ACTIONS
+            .iter()
+            .map(|action| {
+                let keys = match action {
+                    Action::Quit => vec![KeyCode::Esc, KeyCode::Char('q')],
+                    Action::Up => vec![KeyCode::Up],
+                    Action::Down => vec![KeyCode::Down],
+                    Action::PageUp => vec![KeyCode::PageUp],
+                    Action::PageDown => vec![KeyCode::PageDown],
+                    Action::Edit => vec![KeyCode::Char('e')],
+                    Action::Save => vec![KeyCode::Enter],
+                    Action::Back => vec![KeyCode::Char('c')],
+                    Action::Home => vec![KeyCode::Char('h')],
+                    Action::Add => vec![KeyCode::Char('a')],
+                    Action::NewTransaction => vec![KeyCode::Char('t')],
+                    Action::Budgets => vec![KeyCode::Char('m')],
+                    Action::Recurring => vec![KeyCode::Char('r')],
+                    Action::NetWorth => vec![KeyCode::Char('n')],
+                    Action::Charts => vec![KeyCode::Char('v')],
+                    Action::SelectList => vec![KeyCode::Char('l')],
+                    Action::SelectTransactions => vec![KeyCode::Char('s')],
+                    Action::Select => vec![KeyCode::Enter],
+                    Action::StopSelect => vec![KeyCode::Esc],
+                    Action::Delete => vec![KeyCode::Char('d')],
+                    Action::DeleteUser => vec![KeyCode::Char('b')],
+                    Action::Undo => vec![KeyCode::Char('z')],
+                    Action::Redo => vec![KeyCode::Char('y')],
+                    Action::Pause => vec![KeyCode::Char('p')],
+                    Action::CyclePeriod => vec![KeyCode::Char('g')],
+                    Action::Reload => vec![KeyCode::Char('r')],
+                    Action::CycleTheme => vec![KeyCode::Char('T')],
+                    Action::CommandPalette => vec![KeyCode::Char(':')],
+                    Action::Search => vec![KeyCode::Char('/')],
+                    // the digits from 1 up
+                    Action::OpenAccount(idx) | Action::SortColumn(idx) => {
+                        vec![KeyCode::Char(char::from(b'1' + *idx as u8))]
+                    },
+                    Action::Confirm => vec![KeyCode::Char('y')],
+                    Action::Cancel => vec![KeyCode::Char('n')],
+                };
+                (*action, keys)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    // The default keys plus j/k to move, i to edit, u to undo and q to leave
+    // a list
+    pub fn vim() -> Self {
+        let mut keymap = Self::default();
+        keymap.bindings.insert(Action::Up, vec![KeyCode::Char('k'), KeyCode::Up]);
+        keymap.bindings.insert(Action::Down, vec![KeyCode::Char('j'), KeyCode::Down]);
+        keymap.bindings.insert(Action::PageUp, vec![KeyCode::Char('u'), KeyCode::PageUp]);
+        keymap.bindings.insert(Action::PageDown, vec![KeyCode::Char('d'), KeyCode::PageDown]);
+        keymap.bindings.insert(Action::Edit, vec![KeyCode::Char('i'), KeyCode::Char('e')]);
+        keymap.bindings.insert(Action::Undo, vec![KeyCode::Char('u'), KeyCode::Char('z')]);
+        keymap.bindings.insert(Action::StopSelect, vec![KeyCode::Esc, KeyCode::Char('q')]);
+        keymap
+    }
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "vim" => Some(Self::vim()),
+            _ => None,
+        }
+    }
+
+    // Reads the keymap file when there is one, falling back to the default
+    // keys along with the reason when it cannot be used
+    pub fn load() -> (Self, Option<String>) {
+        let path = match std::env::var_os("FINANCE_TRACKER_KEYMAP") {
+            Some(path) => PathBuf::from(path),
+            None => match std::env::var_os("HOME") {
+                Some(home) => PathBuf::from(home).join(KEYMAP_FILE),
+                None => return (Self::default(), None),
+            },
+        };
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return (Self::default(), None);
+        };
+        match Self::parse(&text) {
+            Ok(keymap) => (keymap, None),
+            Err(e) => (Self::default(), Some(format!("Ignored {}: {}", path.display(), e))),
+        }
+    }
+
+    // Parses "preset = vim" and "action = key, key" lines, each action
+    // replacing the keys of the preset. Lines starting with # are comments.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut keymap = Self::default();
+        let mut overrides: Vec<(Action, Vec<KeyCode>)> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, value)) = line.split_once('=') else {
+                return Err(format!("line {} is not name = value", number + 1));
+            };
+            let (name, value) = (name.trim(), value.trim());
+            if name == "preset" {
+                keymap = Self::preset(value).ok_or_else(|| {
+                    format!("line {}: preset must be one of {}", number + 1, KEYMAP_PRESETS.join(", "))
+                })?;
+                continue;
+            }
+            let action = ACTIONS
+                .iter()
+                .find(|a| a.name() == name)
+                .ok_or_else(|| format!("line {}: unknown action {}", number + 1, name))?;
+            let mut keys = Vec::new();
+            for key in value.split(',').map(|k| k.trim()).filter(|k| !k.is_empty()) {
+                keys.push(parse_key(key).ok_or_else(|| format!("line {}: unknown key {}", number + 1, key))?);
+            }
+            overrides.push((*action, keys));
+        }
+        // the preset applies wherever it appears in the file
+        for (action, keys) in overrides {
+            keymap.bindings.insert(action, keys);
+        }
+        Ok(keymap)
+    }
+
+    // The first of the candidate actions the key is bound to
+    pub fn action_for(&self, code: KeyCode, candidates: &[Action]) -> Option<Action> {
+        candidates
+            .iter()
+            .find(|action| self.keys(**action).contains(&code))
+            .copied()
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings.get(&action).map(|keys| keys.as_slice()).unwrap_or(&[])
+    }
+
+    // Keys of the action as shown in the instructions, such as "esc or q"
+    pub fn describe(&self, action: Action) -> String {
+        let names: Vec<String> = self.keys(action).iter().map(key_name).collect();
+        if names.is_empty() {
+            String::from("(unbound)")
+        } else {
+            names.join(" or ")
+        }
+    }
+
+    // First keys of a row of numbered actions, such as "1/2/3/4/5"
+    pub fn describe_each(&self, actions: &[Action]) -> String {
+        let names: Vec<String> = actions
+            .iter()
+            .filter_map(|action| self.keys(*action).first().map(key_name))
+            .collect();
+        if names.is_empty() {
+            String::from("(unbound)")
+        } else {
+            names.join("/")
+        }
+    }
+}
+
+pub fn parse_key(key: &str) -> Option<KeyCode> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    match key.to_lowercase().as_str() {
+        "enter" | "return" => Some(KeyCode::Enter),
+        "esc" | "escape" => Some(KeyCode::Esc),
+        "up" => Some(KeyCode::Up),
+        "down" => Some(KeyCode::Down),
+        "left" => Some(KeyCode::Left),
+        "right" => Some(KeyCode::Right),
+        "pageup" => Some(KeyCode::PageUp),
+        "pagedown" => Some(KeyCode::PageDown),
+        "home" => Some(KeyCode::Home),
+        "end" => Some(KeyCode::End),
+        "tab" => Some(KeyCode::Tab),
+        "backspace" => Some(KeyCode::Backspace),
+        "delete" => Some(KeyCode::Delete),
+        "space" => Some(KeyCode::Char(' ')),
+        name => name.strip_prefix('f').and_then(|n| n.parse().ok()).map(KeyCode::F),
+    }
+}
+
+pub fn key_name(code: &KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => String::from("space"),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => String::from("return"),
+        KeyCode::Esc => String::from("esc"),
+        KeyCode::Up => String::from("up"),
+        KeyCode::Down => String::from("down"),
+        KeyCode::Left => String::from("left"),
+        KeyCode::Right => String::from("right"),
+        KeyCode::PageUp => String::from("page up"),
+        KeyCode::PageDown => String::from("page down"),
+        KeyCode::Home => String::from("home"),
+        KeyCode::End => String::from("end"),
+        KeyCode::Tab => String::from("tab"),
+        KeyCode::Backspace => String::from("backspace"),
+        KeyCode::Delete => String::from("delete"),
+        KeyCode::F(n) => format!("f{}", n),
+        other => format!("{:?}", other).to_lowercase(),
+    }
+}
@@ -5,7 +5,7 @@ use ratatui::{backend::CrosstermBackend, Terminal};
 use crate::{
     app::{App, AppResult},
     event::{Event, EventHandler},
-    handler::handle_key_events,
+    handler::{handle_key_events, handle_mouse_events},
     tui::Tui,
 };
 
@@ -16,6 +16,9 @@ pub mod handler;
 pub mod tui;
 pub mod ui;
 pub mod client;
+pub mod keymap;
+pub mod theme;
+pub mod command;
 
 #[tokio::main]
 async fn main() -> AppResult<()> {
@@ -36,7 +39,7 @@ async fn main() -> AppResult<()> {
         match tui.events.next().await? {
             // Event::Tick => app.tick(),
             Event::Key(key_event) => handle_key_events(key_event, &mut app).await?,
-            Event::Mouse(_) => {}
+            Event::Mouse(mouse_event) => handle_mouse_events(mouse_event, &mut app).await?,
             Event::Resize(_, _) => {}
         }
     }
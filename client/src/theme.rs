@@ -0,0 +1,266 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use ratatui::style::{palette::tailwind::SLATE, Color, Modifier, Style};
+
+// File read at startup, unless FINANCE_TRACKER_THEME points somewhere else
+const THEME_FILE: &str = ".config/finance-tracker/theme.conf";
+
+pub const THEME_COLORS: [&str; 19] = [
+    "fg",
+    "bg",
+    "accent",
+    "accent_fg",
+    "row_bg",
+    "alt_row_bg",
+    "selected_bg",
+    "selected_fg",
+    "list_fg",
+    "focused",
+    "editing",
+    "error",
+    "success",
+    "warning",
+    "income",
+    "expense",
+    "chart",
+    "bar",
+    "matched",
+];
+
+// Colors of every part of the TUI, by what they mean rather than where
+// they are used
+#[derive(Debug, PartialEq, Clone)]
+pub struct Theme {
+    pub name: String,
+    pub fg: Color,
+    pub bg: Color,
+    pub accent: Color, // title bar, list headers and buttons
+    pub accent_fg: Color, // text drawn on the accent color
+    pub row_bg: Color,
+    pub alt_row_bg: Color,
+    pub selected_bg: Color,
+    pub selected_fg: Color,
+    pub list_fg: Color,
+    pub focused: Color, // field the arrows are on
+    pub editing: Color, // field being typed into
+    pub error: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub income: Color,
+    pub expense: Color,
+    pub chart: Color, // lines of the trend charts
+    pub bar: Color, // bars of the spending chart
+    pub matched: Color, // text matching the transaction search
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            name: String::from("dark"),
+            fg: Color::Reset,
+            bg: Color::Reset,
+            accent: Color::Cyan,
+            accent_fg: SLATE.c100,
+            row_bg: Color::Black,
+            alt_row_bg: Color::Black,
+            selected_bg: Color::Yellow,
+            selected_fg: Color::Black,
+            list_fg: Color::Cyan,
+            focused: Color::LightCyan,
+            editing: Color::Yellow,
+            error: Color::Red,
+            success: Color::Green,
+            warning: Color::Yellow,
+            income: Color::Green,
+            expense: Color::Red,
+            chart: Color::Cyan,
+            bar: Color::Yellow,
+            matched: Color::Magenta,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            name: String::from("light"),
+            fg: Color::Black,
+            bg: Color::White,
+            accent: Color::Blue,
+            accent_fg: Color::White,
+            row_bg: Color::White,
+            alt_row_bg: SLATE.c100,
+            selected_bg: Color::Rgb(255, 221, 87),
+            selected_fg: Color::Black,
+            list_fg: Color::Black,
+            focused: Color::Blue,
+            editing: Color::Magenta,
+            error: Color::Red,
+            success: Color::Rgb(0, 128, 0),
+            warning: Color::Rgb(200, 120, 0),
+            income: Color::Rgb(0, 128, 0),
+            expense: Color::Red,
+            chart: Color::Blue,
+            bar: Color::Rgb(200, 120, 0),
+            matched: Color::LightMagenta,
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            name: String::from("high-contrast"),
+            fg: Color::White,
+            bg: Color::Black,
+            accent: Color::Yellow,
+            accent_fg: Color::Black,
+            row_bg: Color::Black,
+            alt_row_bg: Color::Black,
+            selected_bg: Color::White,
+            selected_fg: Color::Black,
+            list_fg: Color::White,
+            focused: Color::LightYellow,
+            editing: Color::LightMagenta,
+            error: Color::LightRed,
+            success: Color::LightGreen,
+            warning: Color::LightYellow,
+            income: Color::LightGreen,
+            expense: Color::LightRed,
+            chart: Color::LightCyan,
+            bar: Color::LightYellow,
+            matched: Color::LightCyan,
+        }
+    }
+
+    pub fn built_in() -> Vec<Self> {
+        vec![Self::dark(), Self::light(), Self::high_contrast()]
+    }
+
+    pub fn base(&self) -> Style {
+        Style::new().fg(self.fg).bg(self.bg)
+    }
+
+    pub fn header(&self) -> Style {
+        Style::new().fg(self.accent_fg).bg(self.accent)
+    }
+
+    pub fn selected(&self) -> Style {
+        Style::new().fg(self.selected_fg).bg(self.selected_bg).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn matched(&self) -> Style {
+        Style::new().fg(Color::Black).bg(self.matched).add_modifier(Modifier::BOLD)
+    }
+
+    // Green for money coming in, red for money going out
+    pub fn amount(&self, amount: f64) -> Color {
+        if amount < 0.0 { self.expense } else { self.income }
+    }
+
+    pub fn trans_type(&self, trans_type: &str) -> Color {
+        if trans_type == "Expenses" { self.expense } else { self.income }
+    }
+
+    // Sets one of THEME_COLORS from a color name, an index or #rrggbb
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let color = Color::from_str(value).map_err(|_| format!("{} is not a color", value))?;
+        let field = match key {
+            "fg" => &mut self.fg,
+            "bg" => &mut self.bg,
+            "accent" => &mut self.accent,
+            "accent_fg" => &mut self.accent_fg,
+            "row_bg" => &mut self.row_bg,
+            "alt_row_bg" => &mut self.alt_row_bg,
+            "selected_bg" => &mut self.selected_bg,
+            "selected_fg" => &mut self.selected_fg,
+            "list_fg" => &mut self.list_fg,
+            "focused" => &mut self.focused,
+            "editing" => &mut self.editing,
+            "error" => &mut self.error,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "income" => &mut self.income,
+            "expense" => &mut self.expense,
+            "chart" => &mut self.chart,
+            "bar" => &mut self.bar,
+            "matched" => &mut self.matched,
+            _ => return Err(format!("unknown color {}, expected one of {}", key, THEME_COLORS.join(", "))),
+        };
+        *field = color;
+        Ok(())
+    }
+
+    // Reads the theme file when there is one, returning the built-in themes
+    // followed by the user's, the index of the one to start with, and the
+    // reason the file was ignored if it could not be used
+    pub fn load() -> (Vec<Self>, usize, Option<String>) {
+        let path = match std::env::var_os("FINANCE_TRACKER_THEME") {
+            Some(path) => PathBuf::from(path),
+            None => match std::env::var_os("HOME") {
+                Some(home) => PathBuf::from(home).join(THEME_FILE),
+                None => return (Self::built_in(), 0, None),
+            },
+        };
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return (Self::built_in(), 0, None);
+        };
+        match Self::parse(&text) {
+            Ok((themes, selected)) => (themes, selected, None),
+            Err(e) => (Self::built_in(), 0, Some(format!("Ignored {}: {}", path.display(), e))),
+        }
+    }
+
+    // Parses "theme = name" choosing the theme to start with, and "[name]"
+    // sections defining a theme from "base = theme" and "color = value" lines.
+    // Lines starting with # are comments.
+    pub fn parse(text: &str) -> Result<(Vec<Self>, usize), String> {
+        let mut themes = Self::built_in();
+        let mut selected = String::from("dark");
+        let mut current: Option<usize> = None;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim().to_string();
+                // a section named after an existing theme changes that theme
+                current = Some(match themes.iter().position(|t| t.name == name) {
+                    Some(idx) => idx,
+                    None => {
+                        themes.push(Theme { name, ..Self::dark() });
+                        themes.len() - 1
+                    }
+                });
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {} is not name = value", number + 1));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match (current, key) {
+                (None, "theme") => selected = value.to_string(),
+                (None, _) => return Err(format!("line {}: {} must be inside a [theme] section", number + 1, key)),
+                (Some(idx), "base") => {
+                    let base = themes
+                        .iter()
+                        .find(|t| t.name == value)
+                        .cloned()
+                        .ok_or_else(|| format!("line {}: unknown theme {}", number + 1, value))?;
+                    let name = themes[idx].name.clone();
+                    themes[idx] = Theme { name, ..base };
+                },
+                (Some(idx), _) => themes[idx].set(key, value).map_err(|e| format!("line {}: {}", number + 1, e))?,
+            }
+        }
+        let selected = themes
+            .iter()
+            .position(|t| t.name == selected)
+            .ok_or_else(|| format!("unknown theme {}", selected))?;
+        Ok((themes, selected))
+    }
+}
@@ -1,52 +1,857 @@
 use ratatui::{
     layout::{Alignment, Constraint, Layout, Rect},
-    style::{Color, Style},
-    widgets::{Block, Paragraph},
+    style::Style,
+    symbols::Marker,
+    widgets::{Axis, Bar, BarChart, BarGroup, Block, Chart, Clear, Dataset, GraphType, LineGauge, Paragraph},
     Frame,
 };
 use ratatui::prelude::*;
+use crossterm::event::KeyCode;
 use crate::app::App;
+use crate::command::suggestions;
+use crate::keymap::{key_name, Action, OPEN_ACCOUNT_ACTIONS, SORT_COLUMN_ACTIONS};
 use crate::input::{ 
     InputMode, 
     Page, 
     InputContent,
+    ListType,
+    SplitRecord,
+    ClickTarget,
 };
 
+// Commands listed under the prompt of the command palette
+const COMMAND_SUGGESTIONS: usize = 6;
 
-// not doing input validation for number inputs yet
 pub fn render_input_field(app: &mut App, frame: &mut Frame, position: Rect, label: String, content_value: String, content: InputContent) {
     let line;
     let style;
     if app.input_mode == InputMode::Editing && app.input_content == content {
         line = format!("{}: {}", label, app.input);
-        style = Style::default().fg(Color::Yellow);
+        style = Style::default().fg(app.theme.editing);
+        if content.is_category() {
+            app.suggestion_area = Some(position);
+        }
     } else if app.input_content == content {
         line = format!("{}: {}", label, content_value);
-        style = Style::default().fg(Color::LightCyan);
+        style = Style::default().fg(app.theme.focused);
     } else {
         line = format!("{}: {}", label, content_value);
         style = Style::default();
     }
-    let input_field = Paragraph::new(line)
+    let mut block = Block::bordered();
+    if let Some(error) = app.input_errors.get(&content) {
+        block = block.title_bottom(Line::from(error.clone()).style(Style::default().fg(app.theme.error)));
+    }
+    let editing = app.input_mode == InputMode::Editing && app.input_content == content;
+    if let (true, Some(choices)) = (editing, content.choices()) {
+        render_selector(app, frame, position, block.style(style), label, choices, content);
+        return;
+    }
+    // the rest of the picked suggestion is shown faded after what is typed
+    let mut spans = vec![Span::raw(line)];
+    if app.suggestion_area == Some(position) {
+        let suggestions = app.category_suggestions();
+        if let Some(suggestion) = suggestions.get(app.suggestion_idx.min(suggestions.len().saturating_sub(1))) {
+            let rest = suggestion.name.chars().skip(app.input.chars().count());
+            if suggestion.name.to_lowercase().starts_with(&app.input.to_lowercase()) {
+                spans.push(Span::raw(rest.collect::<String>()).dim());
+            }
+        }
+    }
+    let input_field = Paragraph::new(Line::from(spans))
         .style(style)
-        .block(Block::bordered());
+        .block(block);
     frame.render_widget(input_field, position);
+    app.hit_regions.push((position, ClickTarget::Field(content)));
+}
+
+// A field being edited that takes one of a few values, drawn with every
+// value on the line and the picked one highlighted. Each value can be
+// clicked.
+fn render_selector(app: &mut App, frame: &mut Frame, position: Rect, block: Block, label: String, choices: Vec<&'static str>, content: InputContent) {
+    let inner = block.inner(position);
+    let mut x = inner.x + label.chars().count() as u16 + 2;
+    let mut spans = vec![Span::raw(format!("{}: ", label))];
+    app.hit_regions.push((position, ClickTarget::Field(content.clone())));
+    for (idx, choice) in choices.iter().enumerate() {
+        // an empty filter matches everything
+        let text = if choice.is_empty() { String::from(" (any) ") } else { format!(" {} ", choice) };
+        let width = text.chars().count() as u16;
+        if *choice == app.input {
+            spans.push(Span::styled(text, app.theme.selected()));
+        } else {
+            spans.push(Span::raw(text));
+        }
+        spans.push(Span::raw(" "));
+        if x < inner.right() {
+            let area = Rect::new(x, inner.y, width.min(inner.right() - x), 1);
+            app.hit_regions.push((area, ClickTarget::Choice(content.clone(), idx)));
+        }
+        x = x.saturating_add(width + 1);
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)).block(block), position);
+}
+
+pub fn render_net_worth(app: &App, frame: &mut Frame, position: Rect) {
+    let lines = [
+        format!("Assets: {:.2}", app.net_worth.total_assets),
+        format!("Liabilities: {:.2}", app.net_worth.total_liabilities),
+        format!("Net Worth: {:.2}", app.net_worth.net_worth),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines.join("\n")).block(Block::bordered().title("Summary")),
+        position,
+    );
+}
+
+pub fn render_net_worth_chart(app: &App, frame: &mut Frame, position: Rect) {
+    let title = format!("{} Net Worth Trend", app.history_granularity);
+    if app.net_worth_history.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No history available").block(Block::bordered().title(title)),
+            position,
+        );
+        return;
+    }
+
+    let points: Vec<(f64, f64)> = app
+        .net_worth_history
+        .iter()
+        .enumerate()
+        .map(|(i, (_, value))| (i as f64, *value))
+        .collect();
+    let min_y = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let max_y = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    // keep a flat line away from the chart borders
+    let margin = ((max_y - min_y) * 0.1).max(1.0);
+    let (min_y, max_y) = (min_y - margin, max_y + margin);
+
+    let first_date = app.net_worth_history[0].0.clone();
+    let mid_date = app.net_worth_history[points.len() / 2].0.clone();
+    let last_date = app.net_worth_history[points.len() - 1].0.clone();
+
+    let dataset = Dataset::default()
+        .name("Net Worth")
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(app.theme.chart))
+        .data(&points);
+
+    let chart = Chart::new(vec![dataset])
+        .block(Block::bordered().title(title))
+        .x_axis(
+            Axis::default()
+                .title("Date")
+                .bounds([0.0, (points.len() - 1).max(1) as f64])
+                .labels([first_date, mid_date, last_date]),
+        )
+        .y_axis(
+            Axis::default()
+                .title("Amount")
+                .bounds([min_y, max_y])
+                .labels([
+                    format!("{:.2}", min_y),
+                    format!("{:.2}", (min_y + max_y) / 2.0),
+                    format!("{:.2}", max_y),
+                ]),
+        );
+    frame.render_widget(chart, position);
+}
+
+// Spending by category and income vs. expenses side by side, with the balance
+// of the account below
+pub fn render_spending_charts(app: &App, frame: &mut Frame, position: Rect) {
+    let block = Block::bordered().title(format!(
+        "Charts of {} ({})", app.new_account.acct_name, app.chart_range
+    ));
+    let inner = block.inner(position);
+    frame.render_widget(block, position);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(inner);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[0]);
+    let charts = &app.spending_charts;
+
+    // one horizontal bar per category, as many as fit
+    let category_block = Block::bordered().title("Spending by Category");
+    if charts.by_category.is_empty() {
+        frame.render_widget(Paragraph::new("No spending in this range").block(category_block), top[0]);
+    } else {
+        let bars: Vec<Bar> = charts
+            .by_category
+            .iter()
+            .map(|(category, spent)| {
+                Bar::default()
+                    .label(Line::from(category.as_str()))
+                    .value(spent.round().max(0.0) as u64)
+                    .text_value(format!("{:.2}", spent))
+                    .style(Style::default().fg(app.theme.bar))
+            })
+            .collect();
+        frame.render_widget(
+            BarChart::default()
+                .block(category_block)
+                .direction(Direction::Horizontal)
+                .bar_width(1)
+                .bar_gap(0)
+                .data(BarGroup::default().bars(&bars)),
+            top[0],
+        );
+    }
+
+    // a pair of bars per month, the latest months when they do not all fit
+    let monthly_block = Block::bordered().title(Line::from(vec![
+        Span::raw("Monthly "),
+        Span::styled("Income", Style::default().fg(app.theme.income)),
+        Span::raw(" vs. "),
+        Span::styled("Expenses", Style::default().fg(app.theme.expense)),
+    ]));
+    if charts.monthly.is_empty() {
+        frame.render_widget(Paragraph::new("No transactions in this range").block(monthly_block), top[1]);
+    } else {
+        let fits = (top[1].width.saturating_sub(2) / 9).max(1) as usize;
+        let groups: Vec<BarGroup> = charts
+            .monthly
+            .iter()
+            .skip(charts.monthly.len().saturating_sub(fits))
+            .map(|(month, income, expenses)| {
+                BarGroup::default()
+                    .label(Line::from(month.get(..7).unwrap_or(month)))
+                    .bars(&[
+                        Bar::default()
+                            .value(income.round().max(0.0) as u64)
+                            .text_value(String::new())
+                            .style(Style::default().fg(app.theme.income)),
+                        Bar::default()
+                            .value(expenses.round().max(0.0) as u64)
+                            .text_value(String::new())
+                            .style(Style::default().fg(app.theme.expense)),
+                    ])
+            })
+            .collect();
+        let mut chart = BarChart::default()
+            .block(monthly_block)
+            .bar_width(3)
+            .bar_gap(1)
+            .group_gap(2);
+        for group in groups {
+            chart = chart.data(group);
+        }
+        frame.render_widget(chart, top[1]);
+    }
+
+    // balance after each day with transactions
+    let balance_title = "Balance over Time";
+    if charts.balance.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No balance history in this range").block(Block::bordered().title(balance_title)),
+            rows[1],
+        );
+        return;
+    }
+    let points: Vec<(f64, f64)> = charts
+        .balance
+        .iter()
+        .enumerate()
+        .map(|(i, (_, value))| (i as f64, *value))
+        .collect();
+    let min_y = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let max_y = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    // keep a flat line away from the chart borders
+    let margin = ((max_y - min_y) * 0.1).max(1.0);
+    let (min_y, max_y) = (min_y - margin, max_y + margin);
+
+    let first_date = charts.balance[0].0.clone();
+    let mid_date = charts.balance[points.len() / 2].0.clone();
+    let last_date = charts.balance[points.len() - 1].0.clone();
+
+    let dataset = Dataset::default()
+        .name("Balance")
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(app.theme.chart))
+        .data(&points);
+
+    let chart = Chart::new(vec![dataset])
+        .block(Block::bordered().title(balance_title))
+        .x_axis(
+            Axis::default()
+                .title("Date")
+                .bounds([0.0, (points.len() - 1).max(1) as f64])
+                .labels([first_date, mid_date, last_date]),
+        )
+        .y_axis(
+            Axis::default()
+                .title("Amount")
+                .bounds([min_y, max_y])
+                .labels([
+                    format!("{:.2}", min_y),
+                    format!("{:.2}", (min_y + max_y) / 2.0),
+                    format!("{:.2}", max_y),
+                ]),
+        );
+    frame.render_widget(chart, rows[1]);
+}
+
+// Accounts with the keys that open them, this month's flow and top categories,
+// schedules due soon and how much of each credit limit is used
+pub fn render_dashboard(app: &mut App, frame: &mut Frame, position: Rect) {
+    let block = Block::bordered().title(format!("Dashboard of {}", app.username));
+    let inner = block.inner(position);
+    frame.render_widget(block, position);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(inner);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[0]);
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[1]);
+
+    // only the first nine accounts have a key of their own
+    let mut account_lines: Vec<Line> = app
+        .accounts
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, account)| {
+            let key = match OPEN_ACCOUNT_ACTIONS.get(i).and_then(|action| app.keymap.keys(*action).first()) {
+                Some(key) => format!("[{}]", key_name(key)),
+                None => String::from("   "),
+            };
+            let color = app.theme.amount(account.balance);
+            Line::from(vec![
+                Span::raw(format!("{} {} ({}): ", key, account.acct_name, account.acct_type)),
+                Span::styled(format!("{:.2}", account.balance), Style::default().fg(color)),
+            ])
+        })
+        .collect();
+    if account_lines.is_empty() {
+        account_lines.push(Line::from(format!("No accounts yet, press {} to create one", app.keymap.describe(Action::Add))));
+    }
+    let accounts_block = Block::bordered().title("Accounts");
+    let accounts_inner = accounts_block.inner(top[0]);
+    frame.render_widget(Paragraph::new(account_lines).block(accounts_block), top[0]);
+    // clicking an account line opens it like its key does
+    for (i, action) in OPEN_ACCOUNT_ACTIONS.iter().enumerate().take(app.accounts.items.len().min(accounts_inner.height as usize)) {
+        if let Some(key) = app.keymap.keys(*action).first().copied() {
+            app.hit_regions.push((
+                Rect::new(accounts_inner.x, accounts_inner.y + i as u16, accounts_inner.width, 1),
+                ClickTarget::Button(key),
+            ));
+        }
+    }
+
+    let dashboard = &app.dashboard;
+
+    let mut month_lines = vec![
+        Line::styled(format!("Income: {:.2}", dashboard.income), Style::default().fg(app.theme.income)),
+        Line::styled(format!("Expenses: {:.2}", dashboard.expenses), Style::default().fg(app.theme.expense)),
+        Line::from(format!("Net: {:.2}", dashboard.income - dashboard.expenses)),
+        Line::from(""),
+        Line::from("Top spending categories:").bold(),
+    ];
+    if dashboard.top_categories.is_empty() {
+        month_lines.push(Line::from("  No spending this month"));
+    }
+    for (category, spent) in dashboard.top_categories.iter() {
+        month_lines.push(Line::from(format!("  {}: {:.2}", category, spent)));
+    }
+    frame.render_widget(
+        Paragraph::new(month_lines).block(Block::bordered().title(format!(
+            "This Month ({})", dashboard.month.get(..7).unwrap_or(&dashboard.month)
+        ))),
+        top[1],
+    );
+
+    let mut upcoming_lines: Vec<Line> = dashboard
+        .upcoming_recurring
+        .iter()
+        .map(|r| Line::from(format!("{} {} {:.2} {} on {}", r.next_date, r.trans_type, r.amount, r.description, r.account_name)))
+        .collect();
+    if upcoming_lines.is_empty() {
+        upcoming_lines.push(Line::from("Nothing due in the next 30 days"));
+    }
+    frame.render_widget(
+        Paragraph::new(upcoming_lines).block(Block::bordered().title("Upcoming Recurring")),
+        bottom[0],
+    );
+
+    // one gauge per credit account
+    let credit_block = Block::bordered().title("Credit Utilization");
+    let credit_inner = credit_block.inner(bottom[1]);
+    frame.render_widget(credit_block, bottom[1]);
+    if dashboard.credit_utilization.is_empty() {
+        frame.render_widget(Paragraph::new("No credit accounts"), credit_inner);
+        return;
+    }
+    for (row, credit) in dashboard.credit_utilization.iter().take(credit_inner.height as usize).enumerate() {
+        let color = if credit.utilization > 0.9 {
+            app.theme.error
+        } else if credit.utilization > 0.3 {
+            app.theme.warning
+        } else {
+            app.theme.success
+        };
+        let label = format!("{}: {:.2} / {:.2} ", credit.acct_name, credit.owed, credit.card_limit);
+        frame.render_widget(
+            LineGauge::default()
+                .filled_style(Style::new().fg(color))
+                .ratio(credit.utilization.clamp(0.0, 1.0))
+                .label(label),
+            Rect::new(credit_inner.x, credit_inner.y + row as u16, credit_inner.width, 1),
+        );
+    }
+}
+
+// Popup over the page asking to confirm a destructive action
+pub fn render_confirmation(app: &App, frame: &mut Frame) {
+    let Some(confirmation) = &app.confirmation else {
+        return;
+    };
+    let mut lines: Vec<Line> = vec![Line::from(confirmation.title.as_str()).bold(), Line::from("")];
+    lines.extend(confirmation.details.iter().map(|detail| Line::from(detail.as_str())));
+    lines.push(Line::from(""));
+    if confirmation.typed_text.is_some() {
+        let style = if app.confirmation_ready() { Style::default().fg(app.theme.success) } else { Style::default().fg(app.theme.editing) };
+        lines.push(Line::styled(format!("> {}", app.input), style));
+        lines.push(Line::from("Press enter to confirm, esc to cancel"));
+    } else {
+        lines.push(Line::from(format!(
+            "Press {} to confirm, {} or esc to cancel",
+            app.keymap.describe(Action::Confirm),
+            app.keymap.describe(Action::Cancel)
+        )));
+    }
+
+    let area = frame.area();
+    let width = area.width.clamp(20, 70);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .style(app.theme.base())
+            .block(Block::bordered().title("Confirm").border_style(Style::default().fg(app.theme.error))),
+        popup,
+    );
+}
+
+// Categories suggested under the category field being typed into, or above
+// it when there is no room below
+pub fn render_category_suggestions(app: &App, frame: &mut Frame) {
+    let Some(field) = app.suggestion_area else {
+        return;
+    };
+    let suggestions = app.category_suggestions();
+    if suggestions.is_empty() {
+        return;
+    }
+    let selected = app.suggestion_idx.min(suggestions.len() - 1);
+    let lines: Vec<Line> = suggestions
+        .iter()
+        .enumerate()
+        .map(|(i, category)| {
+            let usage = match category.use_count {
+                0 => String::from("never used"),
+                1 => format!("used once, {}", category.last_used),
+                n => format!("used {} times, last {}", n, category.last_used),
+            };
+            let line = Line::from(vec![
+                Span::raw(format!("{} ", category.name)),
+                Span::raw(format!(" {}", usage)).style(Style::default().fg(app.theme.focused)),
+            ]);
+            if i == selected { line.style(app.theme.selected()) } else { line }
+        })
+        .collect();
+
+    let area = frame.area();
+    let height = lines.len() as u16 + 2;
+    let y = if field.bottom() + height <= area.bottom() {
+        field.bottom()
+    } else {
+        field.y.saturating_sub(height)
+    };
+    let popup = Rect::new(field.x, y, field.width.min(50), height.min(area.bottom().saturating_sub(y)));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .style(app.theme.base())
+            .block(Block::bordered().title("Categories").border_style(Style::default().fg(app.theme.editing))),
+        popup,
+    );
+}
+
+// Prompt of the command palette above the buttons, with the commands
+// matching what has been typed
+pub fn render_command_palette(app: &App, frame: &mut Frame) {
+    if app.input_mode != InputMode::Command {
+        return;
+    }
+    let matches = suggestions(&app.command_input);
+    let mut lines = vec![Line::from(format!(":{}", app.command_input))];
+    if matches.is_empty() {
+        lines.push(Line::styled("No matching command", Style::default().fg(app.theme.error)));
+    }
+    for (i, spec) in matches.iter().take(COMMAND_SUGGESTIONS).enumerate() {
+        let line = Line::from(vec![
+            Span::raw(format!("{} ", spec.name)),
+            Span::raw(spec.args).italic(),
+            Span::raw(format!("  {}", spec.help)).style(Style::default().fg(app.theme.focused)),
+        ]);
+        // the first one is what tab completes to
+        lines.push(if i == 0 { line.style(app.theme.selected()) } else { line });
+    }
+
+    // the palette sits right above the buttons, which stay clickable
+    let area = frame.area();
+    let bottom = area.bottom().saturating_sub(4);
+    let height = (lines.len() as u16 + 2).min(bottom.saturating_sub(area.y));
+    let popup = Rect::new(area.x, bottom - height, area.width, height);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .style(app.theme.base())
+            .block(Block::bordered().title("Command").border_style(Style::default().fg(app.theme.editing))),
+        popup,
+    );
+    let cursor_x = popup.x + 2 + app.command_input.chars().count() as u16;
+    frame.set_cursor_position((cursor_x.min(popup.right().saturating_sub(2)), popup.y + 1));
+}
+
+// Actions of the current page shown as buttons that can be clicked, each
+// pressing the first key the action is bound to
+fn page_buttons(app: &App) -> Vec<(&'static str, KeyCode)> {
+    if let Some(confirmation) = &app.confirmation {
+        let confirm = match app.keymap.keys(Action::Confirm).first() {
+            Some(key) if confirmation.typed_text.is_none() => *key,
+            _ => KeyCode::Enter,
+        };
+        return vec![("Confirm", confirm), ("Cancel", KeyCode::Esc)];
+    }
+    let actions = match app.input_mode {
+        InputMode::Editing if app.input_content.choices().is_some() => {
+            return vec![("Previous", KeyCode::Left), ("Next", KeyCode::Right), ("Submit", KeyCode::Enter), ("Cancel", KeyCode::Esc)]
+        },
+        InputMode::Editing if app.input_content.is_category() => {
+            return vec![("Submit", KeyCode::Enter), ("Suggestion", KeyCode::Tab), ("Cancel", KeyCode::Esc)]
+        },
+        InputMode::Editing => return vec![("Submit", KeyCode::Enter), ("Cancel", KeyCode::Esc)],
+        InputMode::Command => {
+            return vec![("Run", KeyCode::Enter), ("Complete", KeyCode::Tab), ("Cancel", KeyCode::Esc)]
+        },
+        InputMode::Search => return vec![("Browse", KeyCode::Enter), ("Clear", KeyCode::Esc)],
+        InputMode::ViewAccountList => vec![("Open", Action::Select), ("Done", Action::StopSelect)],
+        InputMode::Normal => {
+            let account_selected = !app.new_account.acct_id.is_empty();
+            let mut actions = match app.page {
+                Page::Login => vec![("Login", Action::Edit)],
+                Page::Dashboard => vec![
+                    ("New Account", Action::Add),
+                    ("Net Worth", Action::NetWorth),
+                    ("Budgets", Action::Budgets),
+                    ("Reload", Action::Reload),
+                    ("Theme", Action::CycleTheme),
+                    ("Accounts", Action::Back),
+                ],
+                Page::AccountDetails => {
+                    let mut actions = vec![
+                        ("Home", Action::Home),
+                        ("New Account", Action::Add),
+                        ("Net Worth", Action::NetWorth),
+                        ("Budgets", Action::Budgets),
+                    ];
+                    if account_selected {
+                        actions.extend([
+                            ("New Transaction", Action::NewTransaction),
+                            ("Search", Action::Search),
+                            ("Recurring", Action::Recurring),
+                            ("Charts", Action::Charts),
+                            ("Save", Action::Save),
+                            ("Delete", Action::Delete),
+                        ]);
+                    }
+                    if !app.undo_stack.is_empty() {
+                        actions.push(("Undo", Action::Undo));
+                    }
+                    if !app.redo_stack.is_empty() {
+                        actions.push(("Redo", Action::Redo));
+                    }
+                    actions
+                },
+                Page::NewAccount | Page::NewTransaction => vec![("Create", Action::Save), ("Back", Action::Back)],
+                Page::EditTransaction => vec![("Save", Action::Save), ("Delete", Action::Delete), ("Back", Action::Back)],
+                Page::Budgets => vec![
+                    ("Save", Action::Save),
+                    ("New", Action::Add),
+                    ("Delete", Action::Delete),
+                    ("Back", Action::Back),
+                ],
+                Page::Recurring => vec![
+                    ("Save", Action::Save),
+                    ("New", Action::Add),
+                    ("Pause/Resume", Action::Pause),
+                    ("Delete", Action::Delete),
+                    ("Back", Action::Back),
+                ],
+                Page::NetWorthTrend => vec![("Period", Action::CyclePeriod), ("Back", Action::Back)],
+                Page::Charts => vec![("Range", Action::CyclePeriod), ("Back", Action::Back)],
+            };
+            actions.push(("Quit", Action::Quit));
+            actions
+        },
+    };
+    // actions left without a key get no button
+    actions
+        .into_iter()
+        .filter_map(|(label, action)| app.keymap.keys(action).first().map(|key| (label, *key)))
+        .collect()
+}
+
+// Key instructions of the current page, naming the keys of the active keymap
+fn page_instructions(app: &App) -> Vec<String> {
+    let keys = |action: Action| app.keymap.describe(action);
+    let press = |action: Action, what: &str| format!("Press {} to {}", keys(action), what);
+    let press_each = |actions: &[Action], what: &str| format!("Press {} to {}", app.keymap.describe_each(actions), what);
+    let mut lines = vec![String::from("Instructions: ")];
+
+    if app.input_mode == InputMode::Editing && app.input_content.choices().is_some() {
+        lines.push(String::from("Press left and right or the first letter to pick a value"));
+        lines.push(String::from("Press return to submit the value, esc to cancel"));
+        return lines;
+    }
+    if app.input_mode == InputMode::Editing {
+        lines.push(String::from("Press return to submit the value"));
+        if app.input_content.is_category() {
+            lines.push(String::from("Press tab to take the suggested category, up and down to pick another"));
+        }
+        return lines;
+    }
+    if app.input_mode == InputMode::ViewAccountList {
+        let item = match app.list_content {
+            ListType::Acct => "account",
+            ListType::Trans => "transaction",
+            ListType::Budget => "budget",
+            ListType::Recurring => "schedule",
+        };
+        lines.push(format!("Press {} and {} to select {}", keys(Action::Up), keys(Action::Down), item));
+        if app.list_content == ListType::Trans {
+            lines.push(format!("Press {} and {} to scroll", keys(Action::PageUp), keys(Action::PageDown)));
+            lines.push(press_each(&SORT_COLUMN_ACTIONS, "sort by a column, again to reverse"));
+            lines.push(press(Action::Search, "search transactions"));
+        }
+        lines.push(press(Action::Select, &format!("open the selected {}", item)));
+        lines.push(press(Action::StopSelect, "exist selection mode"));
+        return lines;
+    }
+    if app.input_mode == InputMode::Search {
+        lines.push(String::from("Type to search the memos, categories and amounts"));
+        lines.push(String::from("Press return to go through the matching transactions"));
+        lines.push(String::from("Press esc to clear the search"));
+        return lines;
+    }
+    if app.input_mode == InputMode::Command {
+        lines.push(String::from("Type a command, press tab to complete it"));
+        lines.push(String::from("Press up and down to go through the commands run before"));
+        lines.push(String::from("Press return to run it, esc to cancel"));
+        return lines;
+    }
+
+    let fields = |what: &str| format!("Press {} and {} to select {}", keys(Action::Up), keys(Action::Down), what);
+    match app.page {
+        Page::Login => {
+            lines.push(press(Action::Edit, "enter username"));
+        },
+        Page::Dashboard => {
+            lines.push(press_each(&OPEN_ACCOUNT_ACTIONS, "open an account"));
+            lines.push(press(Action::SelectList, "select account"));
+            lines.push(press(Action::Add, "create new account"));
+            lines.push(press(Action::NetWorth, "view net worth trend"));
+            lines.push(press(Action::Budgets, "manage monthly budgets"));
+            lines.push(press(Action::Reload, "reload the dashboard"));
+            lines.push(press(Action::CycleTheme, "switch to the next color theme"));
+            lines.push(press(Action::Back, "go to account details page"));
+        },
+        Page::AccountDetails => {
+            if let Some(action) = app.undo_stack.last() {
+                lines.push(press(Action::Undo, &format!("undo {}", action.describe())));
+            }
+            if let Some(action) = app.redo_stack.last() {
+                lines.push(press(Action::Redo, &format!("redo {}", action.describe())));
+            }
+            lines.push(press(Action::Home, "go back to the dashboard"));
+            lines.push(press(Action::CycleTheme, "switch to the next color theme"));
+            lines.push(press(Action::SelectList, "select account"));
+            lines.push(press(Action::Add, "create new account"));
+            lines.push(press(Action::DeleteUser, "move user to the trash"));
+            lines.push(press(Action::NetWorth, "view net worth trend"));
+            if !app.new_account.acct_id.is_empty() {
+                lines.push(press(Action::Charts, "view spending charts of the account"));
+            }
+            lines.push(press(Action::Budgets, "manage monthly budgets"));
+            if !app.new_account.acct_id.is_empty() {
+                lines.push(press(Action::NewTransaction, "create new transaction"));
+                lines.push(press(Action::Recurring, "manage recurring transactions"));
+                lines.push(fields("account info or filter options"));
+                lines.push(press(Action::Edit, "overwrite account info or filter options"));
+                lines.push(press(Action::Save, "save the changes to the account or filter transactions"));
+                lines.push(press(Action::SelectTransactions, "select transaction"));
+                lines.push(press(Action::Search, "search transactions"));
+                lines.push(press_each(&SORT_COLUMN_ACTIONS, "sort transactions by a column, again to reverse"));
+                lines.push(press(Action::Delete, "move account to the trash"));
+            }
+        },
+        Page::NewAccount => {
+            lines.push(fields("account info"));
+            lines.push(press(Action::Edit, "enter account info"));
+            lines.push(press(Action::Back, "to back to account details page"));
+            lines.push(press(Action::Save, "create the account"));
+        },
+        Page::NewTransaction => {
+            lines.push(fields("transaction info"));
+            lines.push(press(Action::Edit, "enter transaction info"));
+            lines.push(press(Action::Back, "to back to account details page"));
+            lines.push(press(Action::Save, "create the transaction"));
+        },
+        Page::EditTransaction => {
+            lines.push(fields("transaction info"));
+            lines.push(press(Action::Edit, "overwrite transaction info"));
+            lines.push(press(Action::Save, "save changes to the transaction"));
+            lines.push(press(Action::Back, "to back to account details page"));
+            lines.push(press(Action::Delete, "move the transaction to the trash"));
+        },
+        Page::Budgets => {
+            lines.push(fields("budget info"));
+            lines.push(press(Action::Edit, "enter budget info"));
+            lines.push(press(Action::Save, "save the budget"));
+            lines.push(press(Action::SelectList, "select an existing budget"));
+            lines.push(press(Action::Add, "start a new budget"));
+            lines.push(press(Action::Delete, "delete the selected budget"));
+            lines.push(press(Action::Back, "to back to account details page"));
+        },
+        Page::Recurring => {
+            lines.push(fields("schedule info"));
+            lines.push(press(Action::Edit, "enter schedule info"));
+            lines.push(press(Action::Save, "save the schedule"));
+            lines.push(press(Action::SelectList, "select an existing schedule"));
+            lines.push(press(Action::Add, "start a new schedule for the selected account"));
+            lines.push(press(Action::Pause, "pause or resume the selected schedule"));
+            lines.push(press(Action::Delete, "delete the selected schedule"));
+            lines.push(press(Action::Back, "to back to account details page"));
+        },
+        Page::NetWorthTrend => {
+            lines.push(press(Action::CyclePeriod, "switch between daily, weekly and monthly periods"));
+            lines.push(press(Action::Back, "to back to account details page"));
+        },
+        Page::Charts => {
+            lines.push(press(Action::CyclePeriod, "switch between 3, 6 and 12 months and all time"));
+            lines.push(press(Action::Back, "to back to account details page"));
+            lines.push(String::from("The charts follow the transaction filters of the account details page"));
+        }
+    }
+    if app.page != Page::Login {
+        lines.push(press(Action::CommandPalette, "type a command"));
+    }
+    lines.push(press(Action::Quit, "exit the application"));
+    lines
+}
+
+pub fn render_buttons(app: &mut App, frame: &mut Frame, position: Rect) {
+    let mut x = position.x;
+    for (label, key) in page_buttons(app) {
+        let text = format!("[ {} ]", label);
+        let width = (text.chars().count() as u16).min(position.right().saturating_sub(x));
+        if width == 0 {
+            break;
+        }
+        let area = Rect::new(x, position.y, width, 1);
+        frame.render_widget(
+            Paragraph::new(text).style(app.theme.header()),
+            area,
+        );
+        app.hit_regions.push((area, ClickTarget::Button(key)));
+        x = (x + width + 1).min(position.right());
+    }
+}
+
+pub fn render_status_bar(app: &App, frame: &mut Frame, position: Rect) {
+    let (line, style) = if app.status.text.is_empty() {
+        (String::from("Ready"), Style::default())
+    } else if app.status.is_error {
+        (format!("[{}] {}", app.status.time, app.status.text), Style::default().fg(app.theme.error))
+    } else {
+        (format!("[{}] {}", app.status.time, app.status.text), Style::default().fg(app.theme.success))
+    };
+    frame.render_widget(
+        Paragraph::new(line).style(style).block(Block::bordered().title("Status")),
+        position,
+    );
 }
 
 /// Renders the user interface widgets.
+pub fn render_transaction_form(app: &mut App, frame: &mut Frame, position: Rect) {
+    let mut constraints = vec![Constraint::Length(1)];
+    constraints.extend([Constraint::Length(3); 8]);
+    constraints.push(Constraint::Min(0));
+    let form_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(position);
+
+    let attachments = if app.new_trans.attachment_count == 0 {
+        "Attach File (path)".to_string()
+    } else {
+        format!("Attach File (path, {} attached)", app.new_trans.attachment_count)
+    };
+
+    render_input_field(app, frame, form_layout[1], "Transaction Description".to_string(), app.new_trans.description.to_string(), InputContent::TransactionDescription);
+    render_input_field(app, frame, form_layout[2], "Date (YYYY-MM-DD, today, yesterday, -3d, mon)".to_string(), app.new_trans.timestamp.to_string(), InputContent::TransactionDate);
+    render_input_field(app, frame, form_layout[3], "Transaction Type (Income/Expenses)".to_string(), app.new_trans.trans_type.to_string(), InputContent::TransactionType);
+    render_input_field(app, frame, form_layout[4], "Transaction Amount".to_string(), app.new_trans.amount.to_string(), InputContent::TransactionAmount);
+    render_input_field(app, frame, form_layout[5], "Transaction Category".to_string(), app.new_trans.category.to_string(), InputContent::TransactionCategory);
+    render_input_field(app, frame, form_layout[6], "Tags (comma-separated)".to_string(), app.new_trans.tags.join(", "), InputContent::TransactionTags);
+    render_input_field(app, frame, form_layout[7], "Splits (category:amount[:memo]; ...)".to_string(), SplitRecord::format_list(&app.new_trans.splits), InputContent::TransactionSplits);
+    render_input_field(app, frame, form_layout[8], attachments, app.attachment_path.to_string(), InputContent::TransactionAttachment);
+}
+
 pub fn render(app: &mut App, frame: &mut Frame) {
+    // regions are recorded again while the frame is drawn
+    app.hit_regions.clear();
+    app.suggestion_area = None;
+    // everything drawn afterwards keeps the theme's colors unless it sets its own
+    frame.render_widget(Block::new().style(app.theme.base()), frame.area());
+
     let vert_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints(vec![
             Constraint::Percentage(5),
             Constraint::Percentage(25),
-            Constraint::Percentage(70),
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(3),
         ])
         .split(frame.area());
 
     let title = vert_layout[0];
     let subtitle = vert_layout[1];
     let content = vert_layout[2];
+    let buttons = vert_layout[3];
+    let status_bar = vert_layout[4];
 
     let content_layout = Layout::default()
         .direction(Direction::Horizontal)
@@ -64,8 +869,8 @@ pub fn render(app: &mut App, frame: &mut Frame) {
         .bold()
         .style(
             Style::new()
-                // .fg(Color::Cyan)
-                .bg(Color::Cyan),
+                .fg(app.theme.accent_fg)
+                .bg(app.theme.accent),
         )
         .alignment(Alignment::Center)
         .block(Block::bordered()),
@@ -73,75 +878,15 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     );
 
     // debug message + key instruction
-    let mut key_instructions: Vec<String> = Vec::new();
-    key_instructions.push(String::from("Instructions: "));
-
-    match app.page {
-        Page::Login => {
-            if app.input_mode == InputMode::Editing {
-                key_instructions.push(String::from("Press return to submit the value"));
-            } else {
-                key_instructions.push(String::from("Press e to enter username"));
-                key_instructions.push(String::from("Press esc or q to exit the application"));
-            }
-        },
-        Page::AccountDetails => {
-            if app.input_mode == InputMode::Editing {
-                key_instructions.push(String::from("Press return to submit the value"));
-            } else if app.input_mode == InputMode::ViewAccountList {
-                key_instructions.push(String::from("Press up and down to select item"));
-                key_instructions.push(String::from("Press return to confirm selection"));
-                key_instructions.push(String::from("Press esc to exist selection mode"));
-            } else {
-                key_instructions.push(String::from("Press l to select account"));
-                key_instructions.push(String::from("Press a to create new account"));
-                key_instructions.push(String::from("Press b to delete user"));
-                if app.new_account.acct_id != "" {
-                    key_instructions.push(String::from("Press t to create new transaction"));
-                    key_instructions.push(String::from("Press up and down to select account info or filter options"));
-                    key_instructions.push(String::from("Press e to overwrite account info or filter options"));
-                    key_instructions.push(String::from("Press enter to save the changes to the account or filter transactions"));    
-                    key_instructions.push(String::from("Press s to select transaction"));
-                    key_instructions.push(String::from("Press d to delete account"));
-                } 
-            }
-        },
-        Page::NewAccount => {
-            key_instructions.push(String::from("Press up and down to select account info"));
-            key_instructions.push(String::from("Press e to enter account info"));
-            key_instructions.push(String::from("Press c to to back to account details page"));
-            key_instructions.push(String::from("Press enter to create the account"));        
-        },
-        Page::NewTransaction => {
-            key_instructions.push(String::from("Press up and down to select transaction info"));
-            key_instructions.push(String::from("Press e to enter transaction info"));
-            key_instructions.push(String::from("Press c to to back to account details page"));
-            key_instructions.push(String::from("Press enter to create the transaction"));
-        },
-        Page::EditTransaction => {
-            key_instructions.push(String::from("Press up and down to select transaction info"));
-            key_instructions.push(String::from("Press e to overwrite transaction info"));
-            key_instructions.push(String::from("Press enter to save changes to the transaction"));
-            key_instructions.push(String::from("Press c to to back to account details page"));
-            key_instructions.push(String::from("Press d to delete the transaction"));
-        }
-    }     
+    let key_instructions = page_instructions(app);
 
     frame.render_widget(
         Paragraph::new(key_instructions.join("\n")).block(Block::bordered()),
         subtitle,
     );
 
-    // // debug messages
-    // frame.render_widget(
-    //     Paragraph::new(format!("input_content {:?}; page {:?}, t_q_list: {:?}, debug_msg: {:?}", 
-    //         app.input_content, 
-    //         app.page,
-    //         app.new_trans_question_list,
-    //         app.debug_msg
-    //     )).block(Block::bordered()),
-    //     subtitle,
-    // );
+    render_status_bar(app, frame, status_bar);
+    render_buttons(app, frame, buttons);
 
 
     let left_content_inner_layout = Layout::default()
@@ -156,15 +901,17 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     let left_content_inner_layout_sub = Layout::default()
         .direction(Direction::Vertical)
         .constraints(vec![
-            Constraint::Percentage(10), // padding
+            Constraint::Percentage(5),  // padding
             Constraint::Percentage(15), // username
-            Constraint::Percentage(10), // padding
-            Constraint::Percentage(55), // list of accounts
-            Constraint::Percentage(10), // list of accounts
+            Constraint::Percentage(5),  // padding
+            Constraint::Percentage(50), // list of accounts
+            Constraint::Percentage(20), // net worth summary
+            Constraint::Percentage(5),  // padding
         ])
         .split(left_content_inner_layout);
     let username_position = left_content_inner_layout_sub[1];
     let account_position = left_content_inner_layout_sub[3];
+    let net_worth_position = left_content_inner_layout_sub[4];
 
     let right_content_inner_layout = Layout::default()
         .direction(Direction::Horizontal)
@@ -192,9 +939,7 @@ pub fn render(app: &mut App, frame: &mut Frame) {
     let right_row_1_position = right_content_inner_layout_sub[1];
     let right_row_2_position = right_content_inner_layout_sub[2];
     let right_row_3_position = right_content_inner_layout_sub[3];
-    let right_row_4_position = right_content_inner_layout_sub[4];
     let right_row_5_position = right_content_inner_layout_sub[5];
-    let right_row_6_position = right_content_inner_layout_sub[6];
     let trans_his_position = right_content_inner_layout_sub[7];
 
     // if app.username.is_empty() {
@@ -208,14 +953,30 @@ pub fn render(app: &mut App, frame: &mut Frame) {
             render_input_field(app, frame, username_position, "Username".to_string(), app.username.to_string(), InputContent::Username);
 
             frame.render_widget(
-                Paragraph::new(format!(
+                Paragraph::new(
                     r#"
 To view accounts for existing user profiles, enter a username that is registered with the system.
 To create a new user profile, enter a non-existing username and an empty profile will be created.
-                    "#)).block(Block::bordered().title("Account Details")),
+                    "#).block(Block::bordered().title("Account Details")),
                 right_content,
             );
         },
+        Page::Dashboard => {
+            // left profile
+            let profile_section = Paragraph::new("").block(Block::bordered().title("Profile Data"));
+            frame.render_widget(profile_section, left_content);
+            // user name
+            frame.render_widget(
+                Paragraph::new(format!("username: {}", app.username)).block(Block::bordered()),
+                username_position,
+            );
+            // accounts
+            app.render_acct_list(account_position, frame.buffer_mut());
+            render_net_worth(app, frame, net_worth_position);
+
+            // right dashboard
+            render_dashboard(app, frame, right_content);
+        },
         Page::AccountDetails => {
             // left profile
             let profile_section = Paragraph::new("").block(Block::bordered().title("Profile Data"));
@@ -230,25 +991,54 @@ To create a new user profile, enter a non-existing username and an empty profile
             // accounts
             app.render_acct_list(account_position, frame.buffer_mut());
 
+            // net worth across all accounts
+            render_net_worth(app, frame, net_worth_position);
 
-            if app.new_account.acct_id == "" {
+            if app.new_account.acct_id.is_empty() {
                 frame.render_widget(
                     Paragraph::new("Please select an account").block(Block::bordered()),
                     right_content,
                 );
             } else {
-                render_input_field(app, frame, right_row_1_position, "Account Name".to_string(), app.new_account.acct_name.to_string(), InputContent::AccountName);
-                render_input_field(app, frame, right_row_2_position, "Account Type (Chequing/Credit)".to_string(), app.new_account.acct_type.to_string(), InputContent::AccountType);
-                render_input_field(app, frame, right_row_3_position, "Card Limit".to_string(), app.new_account.card_limit.to_string(), InputContent::AccountLimit);
-                render_input_field(app, frame, right_row_4_position, "Filter Transaction Type (Income/Expenses)".to_string(), app.filter_trans_type.to_string(), InputContent::FilterTransType);
-                render_input_field(app, frame, right_row_5_position, "Filter Transaction Category".to_string(), app.filter_trans_category.to_string(), InputContent::FilterTransCategory);
+                let mut balance_lines = vec![Line::from(format!("Balance: {}", app.acct_balance))];
+                for alert in app.budget_alerts.iter() {
+                    balance_lines.push(Line::styled(alert.clone(), Style::default().fg(app.theme.error)));
+                }
+
+                // the account form and the tag filters need more rows than the other forms
+                let mut constraints = vec![Constraint::Length(1)];
+                constraints.extend([Constraint::Length(3); 7]);
+                constraints.push(Constraint::Length(balance_lines.len() as u16));
+                constraints.push(Constraint::Min(3));
+                let details_layout = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints(constraints)
+                    .split(right_content_inner_layout);
+
+                render_input_field(app, frame, details_layout[1], "Account Name".to_string(), app.new_account.acct_name.to_string(), InputContent::AccountName);
+                render_input_field(app, frame, details_layout[2], "Account Type (Chequing/Savings/Credit)".to_string(), app.new_account.acct_type.to_string(), InputContent::AccountType);
+                render_input_field(app, frame, details_layout[3], "Card Limit".to_string(), app.new_account.card_limit.to_string(), InputContent::AccountLimit);
+                render_input_field(app, frame, details_layout[4], "Filter Transaction Type (Income/Expenses)".to_string(), app.filter_trans_type.to_string(), InputContent::FilterTransType);
+                render_input_field(app, frame, details_layout[5], "Filter Transaction Category".to_string(), app.filter_trans_category.to_string(), InputContent::FilterTransCategory);
+                render_input_field(app, frame, details_layout[6], "Filter Tags (comma-separated)".to_string(), app.filter_trans_tags.to_string(), InputContent::FilterTransTags);
+                render_input_field(app, frame, details_layout[7], "Filter Tag Match (Any/All)".to_string(), app.filter_tag_match.to_string(), InputContent::FilterTagMatch);
 
                 frame.render_widget(
-                    Paragraph::new(format!("Balance: {}", app.acct_balance)),
-                    right_row_6_position,
+                    Paragraph::new(balance_lines),
+                    details_layout[8],
                 );
 
-                app.render_trans_list(trans_his_position, frame.buffer_mut());
+                // transaction table with the highlighted row described next to it
+                let trans_layout = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints(vec![
+                        Constraint::Percentage(70),
+                        Constraint::Percentage(30),
+                    ])
+                    .spacing(2)
+                    .split(details_layout[9]);
+                app.render_trans_list(trans_layout[0], frame.buffer_mut());
+                app.render_trans_detail(trans_layout[1], frame.buffer_mut());
 
                 frame.render_widget(
                     Paragraph::new("").block(Block::bordered()).block(Block::bordered().title(
@@ -273,7 +1063,7 @@ To create a new user profile, enter a non-existing username and an empty profile
 
             // right data
             render_input_field(app, frame, right_row_1_position, "Account Name".to_string(), app.new_account.acct_name.to_string(), InputContent::AccountName);
-            render_input_field(app, frame, right_row_2_position, "Account Type (Chequing/Credit)".to_string(), app.new_account.acct_type.to_string(), InputContent::AccountType);
+            render_input_field(app, frame, right_row_2_position, "Account Type (Chequing/Savings/Credit)".to_string(), app.new_account.acct_type.to_string(), InputContent::AccountType);
             render_input_field(app, frame, right_row_3_position, "Card Limit".to_string(), app.new_account.card_limit.to_string(), InputContent::AccountLimit);
 
             frame.render_widget(
@@ -295,10 +1085,7 @@ To create a new user profile, enter a non-existing username and an empty profile
             
 
             // right form
-            render_input_field(app, frame, right_row_1_position, "Transaction Description".to_string(), app.new_trans.description.to_string(), InputContent::TransactionDescription);
-            render_input_field(app, frame, right_row_2_position, "Transaction Type (Income/Expenses)".to_string(), app.new_trans.trans_type.to_string(), InputContent::TransactionType);
-            render_input_field(app, frame, right_row_3_position, "Transaction Amount".to_string(), app.new_trans.amount.to_string(), InputContent::TransactionAmount);
-            render_input_field(app, frame, right_row_4_position, "Transaction Category".to_string(), app.new_trans.category.to_string(), InputContent::TransactionCategory);
+            render_transaction_form(app, frame, right_content_inner_layout);
 
             frame.render_widget(
                 Paragraph::new("").block(Block::bordered()).block(Block::bordered().title("Record New Transaction")),
@@ -320,10 +1107,7 @@ To create a new user profile, enter a non-existing username and an empty profile
 
 
             // right form
-            render_input_field(app, frame, right_row_1_position, "Transaction Description".to_string(), app.new_trans.description.to_string(), InputContent::TransactionDescription);
-            render_input_field(app, frame, right_row_2_position, "Transaction Type (Income/Expenses)".to_string(), app.new_trans.trans_type.to_string(), InputContent::TransactionType);
-            render_input_field(app, frame, right_row_3_position, "Transaction Amount".to_string(), app.new_trans.amount.to_string(), InputContent::TransactionAmount);
-            render_input_field(app, frame, right_row_4_position, "Transaction Category".to_string(), app.new_trans.category.to_string(), InputContent::TransactionCategory);
+            render_transaction_form(app, frame, right_content_inner_layout);
 
             frame.render_widget(
                 Paragraph::new("").block(Block::bordered()).block(Block::bordered().title(
@@ -332,7 +1116,119 @@ To create a new user profile, enter a non-existing username and an empty profile
                 right_content,
             );
             
-        }
+        },
+        Page::Budgets => {
+            // left profile
+            let profile_section = Paragraph::new("").block(Block::bordered().title("Profile Data"));
+            frame.render_widget(profile_section, left_content);
+            // user name
+            frame.render_widget(
+                Paragraph::new(format!("username: {}", app.username)).block(Block::bordered()),
+                username_position,
+            );
+            // accounts
+            app.render_acct_list(account_position, frame.buffer_mut());
+            render_net_worth(app, frame, net_worth_position);
+
+            // right form
+            render_input_field(app, frame, right_row_1_position, "Budget Category".to_string(), app.new_budget.category.to_string(), InputContent::BudgetCategory);
+            render_input_field(app, frame, right_row_2_position, "Monthly Amount".to_string(), app.new_budget.budgeted.to_string(), InputContent::BudgetAmount);
+            render_input_field(app, frame, right_row_3_position, "Account ID (empty for all accounts)".to_string(), app.new_budget.account_id.to_string(), InputContent::BudgetAccount);
+
+            // budget progress below the form
+            app.render_budget_gauges(right_row_5_position.union(trans_his_position), frame.buffer_mut());
+
+            frame.render_widget(
+                Paragraph::new("").block(Block::bordered()).block(Block::bordered().title(
+                    if app.new_budget.budget_id.is_empty() {
+                        "New Monthly Budget".to_string()
+                    } else {
+                        format!("Edit Budget {}", app.new_budget.budget_id)
+                    })
+                ),
+                right_content,
+            );
+        },
+        Page::Recurring => {
+            // left profile
+            let profile_section = Paragraph::new("").block(Block::bordered().title("Profile Data"));
+            frame.render_widget(profile_section, left_content);
+            // user name
+            frame.render_widget(
+                Paragraph::new(format!("username: {}", app.username)).block(Block::bordered()),
+                username_position,
+            );
+            // accounts
+            app.render_acct_list(account_position, frame.buffer_mut());
+            render_net_worth(app, frame, net_worth_position);
+
+            // the schedule form has more fields than the other forms
+            let mut constraints = vec![Constraint::Length(1)];
+            constraints.extend([Constraint::Length(3); 7]);
+            constraints.push(Constraint::Min(3));
+            let recurring_layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(constraints)
+                .split(right_content_inner_layout);
+
+            render_input_field(app, frame, recurring_layout[1], "Description".to_string(), app.new_recurring.description.to_string(), InputContent::RecurringDescription);
+            render_input_field(app, frame, recurring_layout[2], "Transaction Type (Income/Expenses)".to_string(), app.new_recurring.trans_type.to_string(), InputContent::RecurringType);
+            render_input_field(app, frame, recurring_layout[3], "Amount".to_string(), app.new_recurring.amount.to_string(), InputContent::RecurringAmount);
+            render_input_field(app, frame, recurring_layout[4], "Category".to_string(), app.new_recurring.category.to_string(), InputContent::RecurringCategory);
+            render_input_field(app, frame, recurring_layout[5], "Frequency (Daily/Weekly/Monthly/Yearly)".to_string(), app.new_recurring.frequency.to_string(), InputContent::RecurringFrequency);
+            render_input_field(app, frame, recurring_layout[6], "Start Date (YYYY-MM-DD)".to_string(), app.new_recurring.start_date.to_string(), InputContent::RecurringStartDate);
+            render_input_field(app, frame, recurring_layout[7], "End Date (YYYY-MM-DD, empty for no end)".to_string(), app.new_recurring.end_date.to_string(), InputContent::RecurringEndDate);
+
+            app.render_recurring_list(recurring_layout[8], frame.buffer_mut());
+
+            frame.render_widget(
+                Paragraph::new("").block(Block::bordered()).block(Block::bordered().title(
+                    if app.new_recurring.recurring_id.is_empty() {
+                        format!("New Recurring Transaction for {}", app.new_recurring.account_name)
+                    } else if app.new_recurring.paused {
+                        format!("Edit Recurring Transaction {} (paused)", app.new_recurring.recurring_id)
+                    } else {
+                        format!("Edit Recurring Transaction {}", app.new_recurring.recurring_id)
+                    })
+                ),
+                right_content,
+            );
+        },
+        Page::NetWorthTrend => {
+            // left profile
+            let profile_section = Paragraph::new("").block(Block::bordered().title("Profile Data"));
+            frame.render_widget(profile_section, left_content);
+            // user name
+            frame.render_widget(
+                Paragraph::new(format!("username: {}", app.username)).block(Block::bordered()),
+                username_position,
+            );
+            // accounts
+            app.render_acct_list(account_position, frame.buffer_mut());
+            render_net_worth(app, frame, net_worth_position);
 
+            // right chart
+            render_net_worth_chart(app, frame, right_content);
+        },
+        Page::Charts => {
+            // left profile
+            let profile_section = Paragraph::new("").block(Block::bordered().title("Profile Data"));
+            frame.render_widget(profile_section, left_content);
+            // user name
+            frame.render_widget(
+                Paragraph::new(format!("username: {}", app.username)).block(Block::bordered()),
+                username_position,
+            );
+            // accounts
+            app.render_acct_list(account_position, frame.buffer_mut());
+            render_net_worth(app, frame, net_worth_position);
+
+            // right charts
+            render_spending_charts(app, frame, right_content);
+        }
     }
+
+    render_category_suggestions(app, frame);
+    render_command_palette(app, frame);
+    render_confirmation(app, frame);
 }
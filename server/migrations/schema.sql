@@ -25,3 +25,183 @@ CREATE TABLE IF NOT EXISTS transactions
     account_id       BIGINT,
     FOREIGN KEY (account_id) REFERENCES accounts(account_id) ON DELETE CASCADE
 );
+
+CREATE TABLE IF NOT EXISTS balance_snapshots
+(
+    snapshot_id   BIGSERIAL PRIMARY KEY,
+    account_id    BIGINT,
+    snapshot_date DATE,
+    balance       DOUBLE PRECISION,
+    UNIQUE (account_id, snapshot_date),
+    FOREIGN KEY (account_id) REFERENCES accounts(account_id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS categories
+(
+    category_id   BIGSERIAL PRIMARY KEY,
+    user_id       BIGINT,
+    category_name TEXT NOT NULL,
+    parent_id     BIGINT,
+    FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_id) REFERENCES categories(category_id) ON DELETE SET NULL
+);
+
+-- "Meal" and "meal" are the same category
+CREATE UNIQUE INDEX IF NOT EXISTS categories_user_name_idx
+    ON categories (user_id, LOWER(category_name));
+
+ALTER TABLE transactions
+    ADD COLUMN IF NOT EXISTS category_id BIGINT
+    REFERENCES categories(category_id) ON DELETE SET NULL;
+
+-- Backfill categories from the legacy free-text column
+INSERT INTO categories (user_id, category_name)
+SELECT DISTINCT ON (a.user_id, LOWER(t.category)) a.user_id, t.category
+FROM transactions t
+JOIN accounts a ON a.account_id=t.account_id
+WHERE t.category_id IS NULL AND t.category <> ''
+ON CONFLICT DO NOTHING;
+
+UPDATE transactions t
+SET category_id=c.category_id
+FROM accounts a, categories c
+WHERE t.account_id=a.account_id AND c.user_id=a.user_id
+  AND LOWER(c.category_name)=LOWER(t.category) AND t.category_id IS NULL;
+
+-- Monthly budget for a category and its sub-categories, optionally limited to
+-- a single account
+CREATE TABLE IF NOT EXISTS budgets
+(
+    budget_id     BIGSERIAL PRIMARY KEY,
+    user_id       BIGINT,
+    category_id   BIGINT NOT NULL,
+    account_id    BIGINT,
+    budget_amount DOUBLE PRECISION,
+    FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (category_id) REFERENCES categories(category_id) ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts(account_id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS budgets_scope_idx
+    ON budgets (user_id, category_id, COALESCE(account_id, 0));
+
+-- Template for a transaction repeating on a schedule. next_date is the next
+-- occurrence the scheduler has not created a transaction for yet.
+CREATE TABLE IF NOT EXISTS recurring_transactions
+(
+    recurring_id     BIGSERIAL PRIMARY KEY,
+    account_id       BIGINT,
+    transaction_type TEXT,
+    category         TEXT,
+    amount           DOUBLE PRECISION,
+    transaction_memo TEXT,
+    frequency        TEXT,
+    start_date       DATE,
+    end_date         DATE,
+    next_date        DATE,
+    paused           BOOLEAN NOT NULL DEFAULT FALSE,
+    FOREIGN KEY (account_id) REFERENCES accounts(account_id) ON DELETE CASCADE
+);
+
+-- Free-form labels, stored lowercase so "Vacation" and "vacation" match
+CREATE TABLE IF NOT EXISTS tags
+(
+    tag_id   BIGSERIAL PRIMARY KEY,
+    user_id  BIGINT,
+    tag_name TEXT NOT NULL,
+    UNIQUE (user_id, tag_name),
+    FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS transaction_tags
+(
+    transaction_id BIGINT,
+    tag_id         BIGINT,
+    PRIMARY KEY (transaction_id, tag_id),
+    FOREIGN KEY (transaction_id) REFERENCES transactions(transaction_id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(tag_id) ON DELETE CASCADE
+);
+
+-- Category lines of a transaction, stored with the same sign as the
+-- transaction amount and adding up to it
+CREATE TABLE IF NOT EXISTS transaction_splits
+(
+    split_id       BIGSERIAL PRIMARY KEY,
+    transaction_id BIGINT NOT NULL,
+    category_id    BIGINT,
+    category       TEXT NOT NULL DEFAULT '',
+    amount         DOUBLE PRECISION NOT NULL,
+    split_memo     TEXT NOT NULL DEFAULT '',
+    FOREIGN KEY (transaction_id) REFERENCES transactions(transaction_id) ON DELETE CASCADE,
+    FOREIGN KEY (category_id) REFERENCES categories(category_id) ON DELETE SET NULL
+);
+
+-- Deleted users, accounts and transactions stay in the trash until restored
+-- or purged; NULL means the row is live
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+
+-- Transactions that are not in the trash, and whose account and user are not
+-- either
+CREATE OR REPLACE VIEW live_transactions AS
+SELECT t.*
+FROM transactions t
+JOIN accounts a ON a.account_id=t.account_id
+JOIN users u ON u.user_id=a.user_id
+WHERE t.deleted_at IS NULL AND a.deleted_at IS NULL AND u.deleted_at IS NULL;
+
+-- One row per category line: the splits of a split transaction, or the
+-- transaction itself when it is not split
+CREATE OR REPLACE VIEW transaction_lines AS
+SELECT t.transaction_id, t.account_id, t.transaction_date, t.transaction_type,
+       s.category_id, s.amount
+FROM live_transactions t
+JOIN transaction_splits s ON s.transaction_id=t.transaction_id
+UNION ALL
+SELECT t.transaction_id, t.account_id, t.transaction_date, t.transaction_type,
+       t.category_id, t.amount
+FROM live_transactions t
+WHERE NOT EXISTS (
+    SELECT 1 FROM transaction_splits s WHERE s.transaction_id=t.transaction_id
+);
+
+-- Receipts and documents, stored with the transaction they belong to
+CREATE TABLE IF NOT EXISTS attachments
+(
+    attachment_id  BIGSERIAL PRIMARY KEY,
+    transaction_id BIGINT NOT NULL,
+    file_name      TEXT NOT NULL,
+    content_type   TEXT NOT NULL,
+    file_size      BIGINT NOT NULL,
+    content        BYTEA NOT NULL,
+    uploaded_at    TIMESTAMP NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (transaction_id) REFERENCES transactions(transaction_id) ON DELETE CASCADE
+);
+
+-- Append-only history of every change, kept after the entity is deleted
+CREATE TABLE IF NOT EXISTS audit_log
+(
+    audit_id     BIGSERIAL PRIMARY KEY,
+    entity_type  TEXT NOT NULL,
+    entity_id    BIGINT NOT NULL,
+    action       TEXT NOT NULL,
+    actor        TEXT NOT NULL,
+    before_value JSONB,
+    after_value  JSONB,
+    changed_at   TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_entity_idx
+    ON audit_log (entity_type, entity_id);
+
+CREATE OR REPLACE FUNCTION audit_log_reject_change() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_log_append_only ON audit_log;
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_reject_change();
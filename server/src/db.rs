@@ -1,6 +1,7 @@
-use chrono::NaiveDate;
+use chrono::{Datelike, Days, Local, Months, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgPool, QueryBuilder};
+use sqlx::{postgres::{PgConnection, PgPool}, PgExecutor, QueryBuilder};
+use std::str::FromStr;
 use strum_macros::{Display, EnumString};
 
 pub const PG_CONNECTION_STR: &str =
@@ -10,7 +11,7 @@ pub const PG_CONNECTION_STR: &str =
 pub enum AccountType {
     Chequing,
     Credit,
-    // Savings,
+    Savings,
 }
 
 #[derive(Display, PartialEq, EnumString, Deserialize)]
@@ -19,10 +20,156 @@ pub enum TransactionType {
     Income,
 }
 
+// Whether a transaction must carry any or all of the requested tags
+#[derive(Debug, Display, EnumString, Deserialize, Default, Clone, Copy)]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+// Entities whose changes are recorded in the audit log
+#[derive(Debug, Display, EnumString, Deserialize, Clone, Copy)]
+pub enum AuditEntity {
+    User,
+    Account,
+    Transaction,
+    Category,
+    Budget,
+    Recurring,
+    Attachment,
+}
+
+impl AuditEntity {
+    // Query returning the current row of an entity as JSON text
+    fn snapshot_query(&self) -> &'static str {
+        match self {
+            AuditEntity::User => "SELECT to_jsonb(u)::text FROM users u WHERE u.user_id=($1)",
+            AuditEntity::Account => "SELECT to_jsonb(a)::text FROM accounts a WHERE a.account_id=($1)",
+            AuditEntity::Transaction => {
+                r#"
+SELECT (to_jsonb(t) || jsonb_build_object(
+    'tags', ARRAY(
+        SELECT g.tag_name
+        FROM transaction_tags tt
+        JOIN tags g ON g.tag_id=tt.tag_id
+        WHERE tt.transaction_id=t.transaction_id
+        ORDER BY g.tag_name
+    ),
+    'splits', (
+        SELECT COALESCE(jsonb_agg(to_jsonb(s) ORDER BY s.split_id), '[]')
+        FROM transaction_splits s
+        WHERE s.transaction_id=t.transaction_id
+    )
+))::text
+FROM transactions t
+WHERE t.transaction_id=($1)
+                "#
+            }
+            AuditEntity::Category => "SELECT to_jsonb(c)::text FROM categories c WHERE c.category_id=($1)",
+            AuditEntity::Budget => "SELECT to_jsonb(b)::text FROM budgets b WHERE b.budget_id=($1)",
+            AuditEntity::Recurring => {
+                "SELECT to_jsonb(r)::text FROM recurring_transactions r WHERE r.recurring_id=($1)"
+            }
+            // the file content is left out of the log
+            AuditEntity::Attachment => {
+                "SELECT (to_jsonb(f) - 'content')::text FROM attachments f WHERE f.attachment_id=($1)"
+            }
+        }
+    }
+}
+
+#[derive(Debug, Display)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Purge,
+}
+
+#[derive(Debug, Display, EnumString, Deserialize)]
+pub enum Granularity {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Granularity {
+    // Postgres interval between two points of a time series
+    fn interval(&self) -> &'static str {
+        match self {
+            Granularity::Daily => "1 day",
+            Granularity::Weekly => "1 week",
+            Granularity::Monthly => "1 month",
+        }
+    }
+
+    // Default look-back window when the caller does not provide a start date
+    fn default_start(&self, end_date: &NaiveDate) -> NaiveDate {
+        match self {
+            Granularity::Daily => *end_date - Days::new(30),
+            Granularity::Weekly => *end_date - Days::new(7 * 12),
+            Granularity::Monthly => *end_date - Months::new(12),
+        }
+    }
+}
+
+#[derive(Debug, Display, EnumString, Deserialize, Clone, Copy)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    // The n-th occurrence of a schedule starting on start_date (the 0-th
+    // occurrence is start_date itself). Months are always counted from the
+    // start date, so a schedule on the 31st lands on the last day of short
+    // months without drifting.
+    fn nth_occurrence(&self, start_date: &NaiveDate, n: u32) -> NaiveDate {
+        match self {
+            Frequency::Daily => *start_date + Days::new(n as u64),
+            Frequency::Weekly => *start_date + Days::new(7 * n as u64),
+            Frequency::Monthly => *start_date + Months::new(n),
+            Frequency::Yearly => *start_date + Months::new(12 * n),
+        }
+    }
+
+    // The first occurrence falling on or after the given date
+    fn first_on_or_after(&self, start_date: &NaiveDate, date: &NaiveDate) -> NaiveDate {
+        if date <= start_date {
+            return *start_date;
+        }
+        let n = match self {
+            Frequency::Daily => (*date - *start_date).num_days() as u32,
+            Frequency::Weekly => ((*date - *start_date).num_days() / 7) as u32,
+            Frequency::Monthly => {
+                ((date.year() - start_date.year()) * 12 + date.month() as i32
+                    - start_date.month() as i32) as u32
+            }
+            Frequency::Yearly => (date.year() - start_date.year()) as u32,
+        };
+        let occurrence = self.nth_occurrence(start_date, n);
+        if occurrence < *date {
+            self.nth_occurrence(start_date, n + 1)
+        } else {
+            occurrence
+        }
+    }
+
+    // The occurrence following the given occurrence
+    fn next_after(&self, start_date: &NaiveDate, occurrence: &NaiveDate) -> NaiveDate {
+        self.first_on_or_after(start_date, &(*occurrence + Days::new(1)))
+    }
+}
+
 #[derive(sqlx::FromRow, Debug)]
 pub struct User {
     pub user_id: i64,
     pub username: String,
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
 #[derive(sqlx::FromRow, Debug, Serialize)]
@@ -34,6 +181,31 @@ pub struct Account {
     pub account_limit: f64,
 }
 
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct AccountBalance {
+    pub account_id: i64,
+    pub user_id: i64,
+    pub account_name: String,
+    pub account_type: String,
+    pub account_limit: f64,
+    pub balance: f64,
+    pub transaction_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserSummary {
+    pub accounts: Vec<AccountBalance>,
+    pub total_assets: f64,
+    pub total_liabilities: f64,
+    pub net_worth: f64,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct NetWorthPoint {
+    pub period_date: NaiveDate,
+    pub net_worth: f64,
+}
+
 #[derive(sqlx::FromRow, Debug, Serialize, Deserialize)]
 pub struct Transaction {
     pub transaction_id: i64,
@@ -43,6 +215,238 @@ pub struct Transaction {
     pub amount: f64,
     pub transaction_memo: String,
     pub account_id: i64,
+    pub category_id: Option<i64>,
+    pub tags: Vec<String>,
+    pub attachment_count: i64,
+    pub balance: f64, // account balance after the transaction, whatever the filters
+    #[sqlx(skip)]
+    pub splits: Vec<TransactionSplit>,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize)]
+pub struct TransactionSplit {
+    pub split_id: i64,
+    pub transaction_id: i64,
+    pub category_id: Option<i64>,
+    pub category: String,
+    pub amount: f64,
+    pub split_memo: String,
+}
+
+// A split line as submitted by the client
+#[derive(Debug, Deserialize)]
+pub struct SplitLine {
+    pub category: String,
+    pub amount: f64,
+    #[serde(default)]
+    pub split_memo: String,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct Category {
+    pub category_id: i64,
+    pub user_id: i64,
+    pub category_name: String,
+    pub parent_id: Option<i64>,
+}
+
+// How often and how lately a category has been used, score weighing each
+// use by how recent it is
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct CategoryUsage {
+    pub category_name: String,
+    pub use_count: i64,
+    pub last_used: Option<NaiveDate>,
+    pub score: f64,
+}
+
+// Total of a category including all of its sub-categories
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct CategoryTotal {
+    pub category_id: i64,
+    pub category_name: String,
+    pub parent_id: Option<i64>,
+    pub total: f64,
+}
+
+// Spending of a category, as a positive amount
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct CategorySpending {
+    pub category_name: String,
+    pub spent: f64,
+}
+
+// Income and expenses of a month, both as positive amounts
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct MonthlyFlow {
+    pub month: NaiveDate,
+    pub income: f64,
+    pub expenses: f64,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct BalancePoint {
+    pub balance_date: NaiveDate,
+    pub balance: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpendingCharts {
+    pub by_category: Vec<CategorySpending>,
+    pub monthly: Vec<MonthlyFlow>,
+    pub balance: Vec<BalancePoint>,
+}
+
+// Amount owed on a credit account against its limit; utilization is 0 when
+// the account has no limit
+#[derive(Debug, Serialize)]
+pub struct CreditUtilization {
+    pub account_id: i64,
+    pub account_name: String,
+    pub owed: f64,
+    pub account_limit: f64,
+    pub utilization: f64,
+}
+
+// Everything shown on the home page of a user, in one response
+#[derive(Debug, Serialize)]
+pub struct Dashboard {
+    pub summary: UserSummary,
+    pub month: MonthlyFlow,
+    pub top_categories: Vec<CategorySpending>,
+    pub upcoming_recurring: Vec<RecurringTransaction>,
+    pub credit_utilization: Vec<CreditUtilization>,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct BudgetStatus {
+    pub budget_id: i64,
+    pub category_id: i64,
+    pub category_name: String,
+    pub account_id: Option<i64>,
+    pub budgeted: f64,
+    pub actual: f64,
+    pub remaining: f64,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct RecurringTransaction {
+    pub recurring_id: i64,
+    pub account_id: i64,
+    pub account_name: String,
+    pub transaction_type: String,
+    pub category: String,
+    pub amount: f64,
+    pub transaction_memo: String,
+    pub frequency: String,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub next_date: NaiveDate,
+    pub paused: bool,
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct Attachment {
+    pub attachment_id: i64,
+    pub transaction_id: i64,
+    pub file_name: String,
+    pub content_type: String,
+    pub file_size: i64,
+    pub uploaded_at: NaiveDateTime,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct AttachmentContent {
+    pub file_name: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+struct AuditRow {
+    audit_id: i64,
+    entity_type: String,
+    entity_id: i64,
+    action: String,
+    actor: String,
+    before_value: Option<String>,
+    after_value: Option<String>,
+    changed_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEntry {
+    pub audit_id: i64,
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub action: String,
+    pub actor: String,
+    pub before_value: Option<serde_json::Value>,
+    pub after_value: Option<serde_json::Value>,
+    pub changed_at: NaiveDateTime,
+}
+
+// A deleted user, account or transaction waiting in the trash
+#[derive(sqlx::FromRow, Debug, Serialize)]
+pub struct TrashItem {
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub description: String,
+    pub deleted_at: NaiveDateTime,
+    pub purge_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransactionResult {
+    pub transaction_id: i64,
+    pub budget_warnings: Vec<String>,
+}
+
+// Categories listed on the dashboard, by amount spent this month
+pub const DASHBOARD_TOP_CATEGORIES: i64 = 5;
+// Days ahead the dashboard looks for recurring transactions
+pub const DASHBOARD_UPCOMING_DAYS: u64 = 30;
+
+// Days a deleted entity stays in the trash before it is purged for good
+pub const TRASH_RETENTION_DAYS: i32 = 30;
+
+// Categories every new user starts with, parents listed before their children
+const DEFAULT_CATEGORIES: [(&str, Option<&str>); 12] = [
+    ("Food", None),
+    ("Groceries", Some("Food")),
+    ("Dining", Some("Food")),
+    ("Housing", None),
+    ("Rent", Some("Housing")),
+    ("Utilities", Some("Housing")),
+    ("Transportation", None),
+    ("Shopping", None),
+    ("Health", None),
+    ("Entertainment", None),
+    ("Income", None),
+    ("Salary", Some("Income")),
+];
+
+pub const MAX_ATTACHMENT_SIZE: usize = 5 * 1024 * 1024;
+
+// File extensions accepted as attachments and the content type of each
+const ATTACHMENT_TYPES: [(&str, &str); 5] = [
+    ("pdf", "application/pdf"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("txt", "text/plain"),
+];
+
+// Splits a comma-separated list of tags into trimmed, lowercase, distinct tags
+pub fn parse_tags(tags: &str) -> Vec<String> {
+    let mut parsed: Vec<String> = Vec::new();
+    for tag in tags.split(',') {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !parsed.contains(&tag) {
+            parsed.push(tag);
+        }
+    }
+    parsed
 }
 
 /*****************************************************************************/
@@ -52,15 +456,131 @@ pub struct Transaction {
 pub async fn query_or_create_user(
     pool: &PgPool,
     username: &str,
+    actor: &str,
 ) -> Result<Vec<Account>, sqlx::Error> {
-    if let Ok(user_id) = user_get_one(pool, username).await {
-        account_get_all_for_user(pool, user_id).await
+    if let Ok(user) = user_get_one_with_trashed(pool, username).await {
+        if user.deleted_at.is_some() {
+            return Err(sqlx::Error::InvalidArgument(format!(
+                "user '{}' is in the trash, restore it before logging in",
+                username
+            )));
+        }
+        account_get_all_for_user(pool, user.user_id).await
     } else {
-        let user_id = user_create(pool, username).await?;
+        let mut tx = pool.begin().await?;
+        let user_id = user_create(&mut *tx, username).await?;
+        audit_record(&mut tx, AuditEntity::User, user_id, actor, None).await?;
+        category_seed_defaults(&mut tx, user_id).await?;
+        tx.commit().await?;
         account_get_all_for_user(pool, user_id).await
     }
 }
 
+// Credit balances are negative while money is owed, so the amount owed on a
+// credit account is the negated balance
+pub async fn query_user_summary(
+    pool: &PgPool,
+    username: &str,
+) -> Result<UserSummary, sqlx::Error> {
+    let user_id = user_get_one(pool, username).await?;
+    let accounts = account_get_all_with_balance_for_user(pool, user_id).await?;
+
+    let mut total_assets = 0.0;
+    let mut total_liabilities = 0.0;
+    for account in accounts.iter() {
+        if account.account_type == AccountType::Credit.to_string() {
+            total_liabilities -= account.balance;
+        } else {
+            total_assets += account.balance;
+        }
+    }
+
+    Ok(UserSummary {
+        accounts,
+        total_assets,
+        total_liabilities,
+        net_worth: total_assets - total_liabilities,
+    })
+}
+
+// Summary of all accounts, this month's income and expenses, the top spending
+// categories of the month, recurring transactions due soon and how much of
+// each credit limit is used
+pub async fn query_dashboard(pool: &PgPool, username: &str) -> Result<Dashboard, sqlx::Error> {
+    let summary = query_user_summary(pool, username).await?;
+    let user_id = user_get_one(pool, username).await?;
+    let today = Local::now().date_naive();
+    let (month_start, next_month) = month_bounds(&today);
+
+    let month = chart_get_monthly_flow_for_user(pool, user_id, &month_start, &next_month).await?;
+    let top_categories = chart_get_top_categories_for_user(
+        pool,
+        user_id,
+        &month_start,
+        &next_month,
+        DASHBOARD_TOP_CATEGORIES,
+    )
+    .await?;
+    let upcoming_recurring = recurring_get_upcoming_for_user(
+        pool,
+        user_id,
+        &(today + Days::new(DASHBOARD_UPCOMING_DAYS)),
+    )
+    .await?;
+
+    let credit_utilization = summary
+        .accounts
+        .iter()
+        .filter(|a| a.account_type == AccountType::Credit.to_string())
+        .map(|a| CreditUtilization {
+            account_id: a.account_id,
+            account_name: a.account_name.clone(),
+            owed: -a.balance,
+            account_limit: a.account_limit,
+            utilization: if a.account_limit > 0.0 { -a.balance / a.account_limit } else { 0.0 },
+        })
+        .collect();
+
+    Ok(Dashboard {
+        summary,
+        month,
+        top_categories,
+        upcoming_recurring,
+        credit_utilization,
+    })
+}
+
+// Net worth at the end of each period between start_date and end_date, taken
+// from the daily balance snapshots where there are any and from transaction
+// history otherwise. Changing a transaction drops the snapshots it affects so
+// back-dated transactions are always reflected.
+// The series is anchored on end_date so the last point is the latest balance.
+pub async fn query_net_worth_history(
+    pool: &PgPool,
+    username: &str,
+    granularity: &Granularity,
+    start_date: &Option<NaiveDate>,
+    end_date: &Option<NaiveDate>,
+) -> Result<Vec<NetWorthPoint>, sqlx::Error> {
+    let user_id = user_get_one(pool, username).await?;
+    let end_date = end_date.unwrap_or_else(|| Local::now().date_naive());
+    let start_date = start_date.unwrap_or_else(|| granularity.default_start(&end_date));
+    net_worth_get_series_for_user(pool, user_id, &start_date, &end_date, granularity).await
+}
+
+pub async fn query_account_balance_on_date(
+    pool: &PgPool,
+    account_id: i64,
+    date: &Option<NaiveDate>,
+) -> Result<f64, sqlx::Error> {
+    let date = date.unwrap_or_else(|| Local::now().date_naive());
+    transaction_get_balance_on_date(pool, account_id, &date).await
+}
+
+pub async fn take_balance_snapshots(pool: &PgPool, date: &NaiveDate) -> Result<u64, sqlx::Error> {
+    balance_snapshot_create_all(pool, date).await
+}
+
 pub async fn create_or_update_account(
     pool: &PgPool,
     account_id: Option<i64>,
@@ -68,14 +588,22 @@ pub async fn create_or_update_account(
     account_name: &str,
     account_type: &AccountType,
     account_limit: i32,
+    actor: &str,
 ) -> Result<i64, sqlx::Error> {
-    if let Some(aid) = account_id {
-        account_update(pool, aid, account_name, account_limit).await
+    let mut tx = pool.begin().await?;
+    let (account_id, before) = if let Some(aid) = account_id {
+        let before = audit_snapshot(&mut *tx, AuditEntity::Account, aid).await?;
+        (account_update(&mut *tx, aid, account_name, account_limit).await?, before)
     } else {
-        account_create(pool, username, account_name, account_type, account_limit).await
-    }
+        let user_id = user_get_one(&mut *tx, username).await?;
+        (account_create(&mut *tx, user_id, account_name, account_type, account_limit).await?, None)
+    };
+    audit_record(&mut tx, AuditEntity::Account, account_id, actor, before).await?;
+    tx.commit().await?;
+    Ok(account_id)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create_or_update_transaction(
     pool: &PgPool,
     transaction_id: Option<i64>,
@@ -85,31 +613,40 @@ pub async fn create_or_update_transaction(
     amount: f64,
     transaction_memo: &str,
     account_id: i64,
-) -> Result<i64, sqlx::Error> {
-    if let Some(tid) = transaction_id {
-        transaction_update(
-            pool,
-            tid,
-            transaction_date,
-            transaction_type,
-            category,
-            amount,
-            transaction_memo,
-            account_id,
-        )
-        .await
+    tags: &Option<Vec<String>>,
+    splits: &Option<Vec<SplitLine>>,
+    actor: &str,
+) -> Result<TransactionResult, sqlx::Error> {
+    // the change, its tags and splits and its audit entry are stored together
+    let mut tx = pool.begin().await?;
+    let transaction_id = transaction_save(
+        &mut tx,
+        transaction_id,
+        transaction_date,
+        transaction_type,
+        category,
+        amount,
+        transaction_memo,
+        account_id,
+        tags,
+        splits,
+        actor,
+    )
+    .await?;
+    tx.commit().await?;
+
+    // warn about budgets only once the transaction counts toward them
+    let budget_warnings = if transaction_type == &TransactionType::Expenses {
+        let category_ids = transaction_line_get_category_ids(pool, transaction_id).await?;
+        budget_get_warnings(pool, account_id, &category_ids, transaction_date).await?
     } else {
-        transaction_create(
-            pool,
-            transaction_date,
-            transaction_type,
-            category,
-            amount,
-            transaction_memo,
-            account_id,
-        )
-        .await
-    }
+        Vec::new()
+    };
+
+    Ok(TransactionResult {
+        transaction_id,
+        budget_warnings,
+    })
 }
 
 pub async fn query_account_transactions(
@@ -117,94 +654,652 @@ pub async fn query_account_transactions(
     account_id: i64,
     transaction_type: &Option<TransactionType>,
     category: &Option<String>,
+    tags: &[String],
+    tag_match: &TagMatch,
 ) -> Result<(Vec<Transaction>, f64), sqlx::Error> {
-    let transactions =
-        transaction_get_all_for_account(pool, account_id, transaction_type, category).await?;
-    let transaction_sum = 
-        transaction_get_sum_for_account(pool, account_id, transaction_type, category).await?;
+    let mut transactions = transaction_get_all_for_account(
+        pool,
+        account_id,
+        transaction_type,
+        category,
+        tags,
+        tag_match,
+    )
+    .await?;
+    let ids: Vec<i64> = transactions.iter().map(|t| t.transaction_id).collect();
+    for split in transaction_split_get_for_transactions(pool, &ids).await? {
+        if let Some(t) = transactions.iter_mut().find(|t| t.transaction_id == split.transaction_id) {
+            t.splits.push(split);
+        }
+    }
+    let transaction_sum = transaction_get_sum_for_account(
+        pool,
+        account_id,
+        transaction_type,
+        category,
+        tags,
+        tag_match,
+    )
+    .await?;
     Ok((transactions, transaction_sum))
 }
 
-pub async fn delete_single_user(pool: &PgPool, username: &str) -> Result<(), sqlx::Error> {
-    let num_deleted = user_delete(pool, username).await?;
-    if num_deleted == 0 {
-        return Err(sqlx::Error::RowNotFound);
+pub async fn add_transaction_tag(
+    pool: &PgPool,
+    transaction_id: i64,
+    tag_name: &str,
+    actor: &str,
+) -> Result<(), sqlx::Error> {
+    let tag_name = tag_name.trim().to_lowercase();
+    if tag_name.is_empty() || tag_name.contains(',') {
+        return Err(sqlx::Error::InvalidArgument(format!(
+            "invalid tag name '{}'",
+            tag_name
+        )));
     }
-    if num_deleted != 1 {
-        panic!("More than one user deleted, username is not unique! database is in a bad state, please contact admin :(");
+    let mut tx = pool.begin().await?;
+    let before = audit_snapshot(&mut *tx, AuditEntity::Transaction, transaction_id).await?;
+    if transaction_tag_add(&mut *tx, transaction_id, &tag_name).await? == 0 {
+        if !transaction_tag_exists(&mut *tx, transaction_id, &tag_name).await? {
+            // the transaction does not exist
+            return Err(sqlx::Error::RowNotFound);
+        }
+        return Ok(());
     }
-    Ok(())
+    audit_record(&mut tx, AuditEntity::Transaction, transaction_id, actor, before).await?;
+    tx.commit().await
 }
 
-pub async fn delete_single_account(pool: &PgPool, account_id: i64) -> Result<(), sqlx::Error> {
-    let num_deleted = account_delete(pool, account_id).await?;
+pub async fn remove_transaction_tag(
+    pool: &PgPool,
+    transaction_id: i64,
+    tag_name: &str,
+    actor: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let before = audit_snapshot(&mut *tx, AuditEntity::Transaction, transaction_id).await?;
+    let num_deleted = transaction_tag_remove(&mut *tx, transaction_id, &tag_name.trim().to_lowercase()).await?;
     if num_deleted == 0 {
         return Err(sqlx::Error::RowNotFound);
     }
-    if num_deleted != 1 {
-        panic!("More than one account deleted, account_id is not unique! database is in a bad state, please contact admin :(");
-    }
-    Ok(())
+    audit_record(&mut tx, AuditEntity::Transaction, transaction_id, actor, before).await?;
+    tx.commit().await
 }
 
-pub async fn delete_single_transaction(
+pub async fn query_attachments(
+    pool: &PgPool,
+    transaction_id: i64,
+) -> Result<Vec<Attachment>, sqlx::Error> {
+    attachment_get_all_for_transaction(pool, transaction_id).await
+}
+
+// Stores a file under its base name; the content type is derived from the
+// file extension, which must be one of the supported document types
+pub async fn create_attachment(
     pool: &PgPool,
     transaction_id: i64,
+    file_name: &str,
+    content: &[u8],
+    actor: &str,
+) -> Result<i64, sqlx::Error> {
+    let file_name = file_name.rsplit(['/', '\\']).next().unwrap_or("").trim();
+    if file_name.is_empty() {
+        return Err(sqlx::Error::InvalidArgument("attachment needs a file name".to_string()));
+    }
+    if content.is_empty() {
+        return Err(sqlx::Error::InvalidArgument("attachment is empty".to_string()));
+    }
+    if content.len() > MAX_ATTACHMENT_SIZE {
+        return Err(sqlx::Error::InvalidArgument(format!(
+            "attachment is larger than {} bytes",
+            MAX_ATTACHMENT_SIZE
+        )));
+    }
+    let extension = file_name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+    let Some((_, content_type)) = ATTACHMENT_TYPES.iter().find(|(ext, _)| *ext == extension) else {
+        return Err(sqlx::Error::InvalidArgument(format!(
+            "unsupported attachment type '{}'",
+            extension
+        )));
+    };
+
+    let mut tx = pool.begin().await?;
+    let attachment_id = attachment_create(&mut *tx, transaction_id, file_name, content_type, content).await?;
+    audit_record(&mut tx, AuditEntity::Attachment, attachment_id, actor, None).await?;
+    tx.commit().await?;
+    Ok(attachment_id)
+}
+
+pub async fn query_attachment_content(
+    pool: &PgPool,
+    attachment_id: i64,
+) -> Result<AttachmentContent, sqlx::Error> {
+    attachment_get_content(pool, attachment_id).await
+}
+
+pub async fn delete_single_attachment(
+    pool: &PgPool,
+    attachment_id: i64,
+    actor: &str,
 ) -> Result<(), sqlx::Error> {
-    let num_deleted = transaction_delete(pool, transaction_id).await?;
+    let mut tx = pool.begin().await?;
+    let before = audit_snapshot(&mut *tx, AuditEntity::Attachment, attachment_id).await?;
+    let num_deleted = attachment_delete(&mut *tx, attachment_id).await?;
     if num_deleted == 0 {
         return Err(sqlx::Error::RowNotFound);
     }
-    if num_deleted != 1 {
-        panic!("More than one transaction deleted, transaction_id is not unique! database is in a bad state, please contact admin :(");
-    }
-    Ok(())
+    audit_record(&mut tx, AuditEntity::Attachment, attachment_id, actor, before).await?;
+    tx.commit().await
 }
 
-/*****************************************************************************/
-/*                                User APIs                                  */
-/*****************************************************************************/
-
-async fn user_create(pool: &PgPool, username: &str) -> Result<i64, sqlx::Error> {
-    let rec: (i64,) = sqlx::query_as(
-        r#"
-INSERT INTO users (username)
-VALUES ($1)
-RETURNING user_id
-        "#,
-    )
-    .bind(username)
-    .fetch_one(pool)
-    .await?;
+pub async fn query_categories(pool: &PgPool, username: &str) -> Result<Vec<Category>, sqlx::Error> {
+    let user_id = user_get_one(pool, username).await?;
+    category_get_all_for_user(pool, user_id).await
+}
 
-    Ok(rec.0)
+// Every category of the user, the ones used the most and the most lately
+// first
+pub async fn query_category_usage(pool: &PgPool, username: &str) -> Result<Vec<CategoryUsage>, sqlx::Error> {
+    let user_id = user_get_one(pool, username).await?;
+    category_get_usage_for_user(pool, user_id).await
 }
 
-async fn user_delete(pool: &PgPool, username: &str) -> Result<u64, sqlx::Error> {
-    let rows = sqlx::query(
-        r#"
-DELETE FROM users
-WHERE username=($1)
-        "#,
-    )
-    .bind(username)
-    .execute(pool)
-    .await?
-    .rows_affected();
+// Totals for every category of the account owner, where each parent also
+// includes the transactions of its sub-categories
+pub async fn query_category_report(
+    pool: &PgPool,
+    account_id: i64,
+    tags: &[String],
+    tag_match: &TagMatch,
+) -> Result<Vec<CategoryTotal>, sqlx::Error> {
+    category_get_totals_for_account(pool, account_id, tags, tag_match).await
+}
 
-    Ok(rows)
+// Aggregates behind the charts of an account, for the transactions matching
+// the filters between start_date (the first transaction when none) and
+// end_date (today when none). The balance keeps counting the matching
+// transactions from before start_date so it starts at the right level.
+#[allow(clippy::too_many_arguments)]
+pub async fn query_spending_charts(
+    pool: &PgPool,
+    account_id: i64,
+    transaction_type: &Option<TransactionType>,
+    category: &Option<String>,
+    tags: &[String],
+    tag_match: &TagMatch,
+    start_date: &Option<NaiveDate>,
+    end_date: &Option<NaiveDate>,
+) -> Result<SpendingCharts, sqlx::Error> {
+    let end_date = end_date.unwrap_or_else(|| Local::now().date_naive());
+    let filters = (transaction_type, category, tags, tag_match);
+    Ok(SpendingCharts {
+        by_category: chart_get_spending_by_category(pool, account_id, filters, start_date, &end_date).await?,
+        monthly: chart_get_monthly_flow(pool, account_id, filters, start_date, &end_date).await?,
+        balance: chart_get_balance_series(pool, account_id, filters, start_date, &end_date).await?,
+    })
 }
 
-async fn user_get_one(pool: &PgPool, username: &str) -> Result<i64, sqlx::Error> {
-    let user: User = sqlx::query_as(
-        r#"
-SELECT *
-FROM users
-WHERE username=($1)
-        "#,
-    )
-    .bind(username)
-    .fetch_one(pool)
+pub async fn create_or_update_category(
+    pool: &PgPool,
+    category_id: Option<i64>,
+    username: &str,
+    category_name: &str,
+    parent_id: Option<i64>,
+    actor: &str,
+) -> Result<i64, sqlx::Error> {
+    let user_id = user_get_one(pool, username).await?;
+    let category_name = category_name.trim();
+    if category_name.is_empty() {
+        return Err(sqlx::Error::InvalidArgument("category name cannot be empty".to_string()));
+    }
+
+    if let Some(pid) = parent_id {
+        let parent = category_get_one(pool, pid).await?;
+        if parent.user_id != user_id {
+            return Err(sqlx::Error::InvalidArgument("parent category belongs to another user".to_string()));
+        }
+        // a category cannot be moved under itself or one of its descendants
+        if let Some(cid) = category_id {
+            if category_get_subtree_ids(pool, cid).await?.contains(&pid) {
+                return Err(sqlx::Error::InvalidArgument("parent category would create a cycle".to_string()));
+            }
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    let (category_id, before) = if let Some(cid) = category_id {
+        let before = audit_snapshot(&mut *tx, AuditEntity::Category, cid).await?;
+        (category_update(&mut *tx, cid, category_name, parent_id).await?, before)
+    } else {
+        (category_create(&mut *tx, user_id, category_name, parent_id).await?, None)
+    };
+    audit_record(&mut tx, AuditEntity::Category, category_id, actor, before).await?;
+    tx.commit().await?;
+    Ok(category_id)
+}
+
+pub async fn delete_single_category(
+    pool: &PgPool,
+    category_id: i64,
+    actor: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let before = audit_snapshot(&mut *tx, AuditEntity::Category, category_id).await?;
+    let num_deleted = category_delete(&mut *tx, category_id).await?;
+    if num_deleted == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    audit_record(&mut tx, AuditEntity::Category, category_id, actor, before).await?;
+    tx.commit().await
+}
+
+// Budgeted vs. actual spending for the month containing the given date
+pub async fn query_budgets(
+    pool: &PgPool,
+    username: &str,
+    month: &Option<NaiveDate>,
+) -> Result<Vec<BudgetStatus>, sqlx::Error> {
+    let user_id = user_get_one(pool, username).await?;
+    let month = month.unwrap_or_else(|| Local::now().date_naive());
+    budget_get_status_for_user(pool, user_id, &month).await
+}
+
+pub async fn create_or_update_budget(
+    pool: &PgPool,
+    budget_id: Option<i64>,
+    username: &str,
+    category_name: &str,
+    account_id: Option<i64>,
+    budget_amount: f64,
+    actor: &str,
+) -> Result<i64, sqlx::Error> {
+    if budget_amount < 0.0 {
+        return Err(sqlx::Error::InvalidArgument("budget amount cannot be negative".to_string()));
+    }
+    let user_id = user_get_one(pool, username).await?;
+    let category = category_get_by_name_for_user(pool, user_id, category_name.trim()).await?;
+
+    let mut tx = pool.begin().await?;
+    let (budget_id, before) = if let Some(bid) = budget_id {
+        let before = audit_snapshot(&mut *tx, AuditEntity::Budget, bid).await?;
+        (budget_update(&mut *tx, bid, category.category_id, account_id, budget_amount).await?, before)
+    } else {
+        (budget_create(&mut *tx, user_id, category.category_id, account_id, budget_amount).await?, None)
+    };
+    audit_record(&mut tx, AuditEntity::Budget, budget_id, actor, before).await?;
+    tx.commit().await?;
+    Ok(budget_id)
+}
+
+pub async fn delete_single_budget(
+    pool: &PgPool,
+    budget_id: i64,
+    actor: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let before = audit_snapshot(&mut *tx, AuditEntity::Budget, budget_id).await?;
+    let num_deleted = budget_delete(&mut *tx, budget_id).await?;
+    if num_deleted == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    audit_record(&mut tx, AuditEntity::Budget, budget_id, actor, before).await?;
+    tx.commit().await
+}
+
+pub async fn query_recurring(
+    pool: &PgPool,
+    username: &str,
+) -> Result<Vec<RecurringTransaction>, sqlx::Error> {
+    let user_id = user_get_one(pool, username).await?;
+    recurring_get_all_for_user(pool, user_id).await
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn create_or_update_recurring(
+    pool: &PgPool,
+    recurring_id: Option<i64>,
+    account_id: i64,
+    transaction_type: &TransactionType,
+    category: &str,
+    amount: f64,
+    transaction_memo: &str,
+    frequency: &Frequency,
+    start_date: &NaiveDate,
+    end_date: &Option<NaiveDate>,
+    actor: &str,
+) -> Result<i64, sqlx::Error> {
+    if let Some(end) = end_date {
+        if end < start_date {
+            return Err(sqlx::Error::InvalidArgument("end date is before start date".to_string()));
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    let (recurring_id, before) = if let Some(rid) = recurring_id {
+        // keep the occurrences already created and continue the new rule from
+        // the first occurrence not created yet
+        let existing = recurring_get_one(&mut *tx, rid).await?;
+        let next_date = frequency.first_on_or_after(start_date, &existing.next_date);
+        let before = audit_snapshot(&mut *tx, AuditEntity::Recurring, rid).await?;
+        let rid = recurring_update(
+            &mut *tx,
+            rid,
+            account_id,
+            transaction_type,
+            category,
+            amount,
+            transaction_memo,
+            frequency,
+            start_date,
+            end_date,
+            &next_date,
+        )
+        .await?;
+        (rid, before)
+    } else {
+        let rid = recurring_create(
+            &mut *tx,
+            account_id,
+            transaction_type,
+            category,
+            amount,
+            transaction_memo,
+            frequency,
+            start_date,
+            end_date,
+        )
+        .await?;
+        (rid, None)
+    };
+    audit_record(&mut tx, AuditEntity::Recurring, recurring_id, actor, before).await?;
+    tx.commit().await?;
+    Ok(recurring_id)
+}
+
+// Resuming a schedule skips the occurrences missed while it was paused
+pub async fn set_recurring_paused(
+    pool: &PgPool,
+    recurring_id: i64,
+    paused: bool,
+    actor: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let before = audit_snapshot(&mut *tx, AuditEntity::Recurring, recurring_id).await?;
+    let existing = recurring_get_one(&mut *tx, recurring_id).await?;
+    let next_date = if paused {
+        existing.next_date
+    } else {
+        let frequency = Frequency::from_str(&existing.frequency)
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        let today = Local::now().date_naive();
+        frequency.first_on_or_after(&existing.start_date, &today.max(existing.next_date))
+    };
+    recurring_set_paused(&mut *tx, recurring_id, paused, &next_date).await?;
+    audit_record(&mut tx, AuditEntity::Recurring, recurring_id, actor, before).await?;
+    tx.commit().await
+}
+
+pub async fn delete_single_recurring(
+    pool: &PgPool,
+    recurring_id: i64,
+    actor: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let before = audit_snapshot(&mut *tx, AuditEntity::Recurring, recurring_id).await?;
+    let num_deleted = recurring_delete(&mut *tx, recurring_id).await?;
+    if num_deleted == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    audit_record(&mut tx, AuditEntity::Recurring, recurring_id, actor, before).await?;
+    tx.commit().await
+}
+
+// Creates a transaction for every occurrence due up to today, including the
+// ones missed while the server was down. Each occurrence is claimed by moving
+// next_date forward in the same DB transaction that creates it, so an
+// occurrence is never created twice even if two schedulers run at the same
+// time, and a failed creation leaves it due.
+pub async fn materialize_recurring_transactions(
+    pool: &PgPool,
+    today: &NaiveDate,
+    actor: &str,
+) -> Result<u64, sqlx::Error> {
+    let mut created = 0;
+    for recurring in recurring_get_due(pool, today).await? {
+        let frequency = Frequency::from_str(&recurring.frequency)
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        let transaction_type = TransactionType::from_str(&recurring.transaction_type)
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+        let mut occurrence = recurring.next_date;
+        while occurrence <= *today && recurring.end_date.is_none_or(|end| occurrence <= end) {
+            let following = frequency.next_after(&recurring.start_date, &occurrence);
+            let mut tx = pool.begin().await?;
+            let before = audit_snapshot(&mut *tx, AuditEntity::Recurring, recurring.recurring_id).await?;
+            if recurring_advance(&mut *tx, recurring.recurring_id, &occurrence, &following).await? == 0 {
+                // another scheduler claimed this occurrence
+                break;
+            }
+            audit_record(&mut tx, AuditEntity::Recurring, recurring.recurring_id, actor, before).await?;
+            transaction_save(
+                &mut tx,
+                None,
+                &occurrence,
+                &transaction_type,
+                &recurring.category,
+                recurring.amount,
+                &recurring.transaction_memo,
+                recurring.account_id,
+                &None,
+                &None,
+                actor,
+            )
+            .await?;
+            tx.commit().await?;
+            created += 1;
+            occurrence = following;
+        }
+    }
+
+    Ok(created)
+}
+
+// Trashing a user or an account hides its accounts and transactions without
+// moving them to the trash, so only the entity itself is logged as deleted
+pub async fn delete_single_user(pool: &PgPool, username: &str, actor: &str) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let user_id = user_get_one(&mut *tx, username).await?;
+    let before = audit_snapshot(&mut *tx, AuditEntity::User, user_id).await?;
+    let num_deleted = user_trash(&mut *tx, username).await?;
+    if num_deleted == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    if num_deleted != 1 {
+        panic!("More than one user deleted, username is not unique! database is in a bad state, please contact admin :(");
+    }
+    audit_record_action(&mut tx, AuditEntity::User, user_id, actor, AuditAction::Delete, before).await?;
+    tx.commit().await
+}
+
+pub async fn delete_single_account(
+    pool: &PgPool,
+    account_id: i64,
+    actor: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let before = audit_snapshot(&mut *tx, AuditEntity::Account, account_id).await?;
+    let num_deleted = account_trash(&mut *tx, account_id).await?;
+    if num_deleted == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    if num_deleted != 1 {
+        panic!("More than one account deleted, account_id is not unique! database is in a bad state, please contact admin :(");
+    }
+    audit_record_action(&mut tx, AuditEntity::Account, account_id, actor, AuditAction::Delete, before).await?;
+    tx.commit().await
+}
+
+pub async fn delete_single_transaction(
+    pool: &PgPool,
+    transaction_id: i64,
+    actor: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let before = audit_snapshot(&mut *tx, AuditEntity::Transaction, transaction_id).await?;
+    let num_deleted = transaction_trash(&mut *tx, transaction_id).await?;
+    if num_deleted == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    if num_deleted != 1 {
+        panic!("More than one transaction deleted, transaction_id is not unique! database is in a bad state, please contact admin :(");
+    }
+    balance_snapshot_delete_for_transaction(&mut *tx, transaction_id).await?;
+    audit_record_action(&mut tx, AuditEntity::Transaction, transaction_id, actor, AuditAction::Delete, before).await?;
+    tx.commit().await
+}
+
+// Everything of the user that is in the trash, most recently deleted first.
+// Works for a user that is in the trash itself so it can be restored.
+pub async fn query_trash(pool: &PgPool, username: &str) -> Result<Vec<TrashItem>, sqlx::Error> {
+    let user = user_get_one_with_trashed(pool, username).await?;
+    trash_get_all_for_user(pool, user.user_id).await
+}
+
+// Takes a user, account or transaction back out of the trash. An account or
+// transaction whose user or account is still in the trash would stay hidden,
+// so the parent has to be restored first.
+pub async fn restore_from_trash(
+    pool: &PgPool,
+    entity: AuditEntity,
+    entity_id: i64,
+    actor: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    if let Some(parent) = trash_get_trashed_parent(&mut *tx, entity, entity_id).await? {
+        return Err(sqlx::Error::InvalidArgument(format!(
+            "the {} of this {} is in the trash, restore it first",
+            parent.to_lowercase(),
+            entity.to_string().to_lowercase()
+        )));
+    }
+    let before = audit_snapshot(&mut *tx, entity, entity_id).await?;
+    let num_restored = trash_restore(&mut *tx, entity, entity_id).await?;
+    if num_restored == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    if matches!(entity, AuditEntity::Transaction) {
+        balance_snapshot_delete_for_transaction(&mut *tx, entity_id).await?;
+    }
+    audit_record_action(&mut tx, entity, entity_id, actor, AuditAction::Restore, before).await?;
+    tx.commit().await
+}
+
+// Permanently deletes everything that has been in the trash longer than the
+// retention period, returning how many entities were purged. The rows deleted
+// along with a purged entity by the foreign key cascades are logged as purged
+// too.
+pub async fn purge_trash(pool: &PgPool, actor: &str) -> Result<u64, sqlx::Error> {
+    let mut num_purged = 0;
+    // children first so a child in the trash is logged once, not again with
+    // its parent's cascade
+    for entity in [AuditEntity::Transaction, AuditEntity::Account, AuditEntity::User] {
+        for entity_id in trash_get_expired(pool, entity).await? {
+            let mut tx = pool.begin().await?;
+            let mut purged = Vec::new();
+            for (cascaded, cascaded_id) in trash_get_cascaded(&mut *tx, entity, entity_id).await? {
+                let cascaded = AuditEntity::from_str(&cascaded)
+                    .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+                let before = audit_snapshot(&mut *tx, cascaded, cascaded_id).await?;
+                purged.push((cascaded, cascaded_id, before));
+            }
+            let before = audit_snapshot(&mut *tx, entity, entity_id).await?;
+            purged.push((entity, entity_id, before));
+
+            num_purged += trash_purge(&mut *tx, entity, entity_id).await?;
+            for (purged_entity, purged_id, before) in purged {
+                audit_record_action(&mut tx, purged_entity, purged_id, actor, AuditAction::Purge, before).await?;
+            }
+            tx.commit().await?;
+        }
+    }
+
+    Ok(num_purged)
+}
+
+// Every recorded change of an entity, oldest first
+pub async fn query_audit_history(
+    pool: &PgPool,
+    entity: AuditEntity,
+    entity_id: i64,
+) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    let rows = audit_get_all_for_entity(pool, entity, entity_id).await?;
+    rows.into_iter()
+        .map(|row| {
+            let parse = |value: Option<String>| {
+                value
+                    .map(|v| serde_json::from_str(&v))
+                    .transpose()
+                    .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+            };
+            Ok(AuditEntry {
+                audit_id: row.audit_id,
+                entity_type: row.entity_type,
+                entity_id: row.entity_id,
+                action: row.action,
+                actor: row.actor,
+                before_value: parse(row.before_value)?,
+                after_value: parse(row.after_value)?,
+                changed_at: row.changed_at,
+            })
+        })
+        .collect()
+}
+
+/*****************************************************************************/
+/*                                User APIs                                  */
+/*****************************************************************************/
+
+async fn user_create(executor: impl PgExecutor<'_>, username: &str) -> Result<i64, sqlx::Error> {
+    let rec: (i64,) = sqlx::query_as(
+        r#"
+INSERT INTO users (username)
+VALUES ($1)
+RETURNING user_id
+        "#,
+    )
+    .bind(username)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(rec.0)
+}
+
+async fn user_trash(executor: impl PgExecutor<'_>, username: &str) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+UPDATE users
+SET deleted_at=NOW()
+WHERE username=($1) AND deleted_at IS NULL
+        "#,
+    )
+    .bind(username)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+async fn user_get_one(executor: impl PgExecutor<'_>, username: &str) -> Result<i64, sqlx::Error> {
+    let user: User = sqlx::query_as(
+        r#"
+SELECT *
+FROM users
+WHERE username=($1) AND deleted_at IS NULL
+        "#,
+    )
+    .bind(username)
+    .fetch_one(executor)
     .await?;
 
     let _username = user.username.clone();
@@ -212,18 +1307,33 @@ WHERE username=($1)
     Ok(user.user_id)
 }
 
+// Looks the user up whether or not it is in the trash
+async fn user_get_one_with_trashed(pool: &PgPool, username: &str) -> Result<User, sqlx::Error> {
+    let user: User = sqlx::query_as(
+        r#"
+SELECT user_id, username, deleted_at
+FROM users
+WHERE username=($1)
+        "#,
+    )
+    .bind(username)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(user)
+}
+
 /*****************************************************************************/
 /*                               Account APIs                                */
 /*****************************************************************************/
 
 async fn account_create(
-    pool: &PgPool,
-    username: &str,
+    executor: impl PgExecutor<'_>,
+    user_id: i64,
     account_name: &str,
     account_type: &AccountType,
     account_limit: i32,
 ) -> Result<i64, sqlx::Error> {
-    let user_id = user_get_one(pool, username).await?;
     let rec: (i64,) = sqlx::query_as(
         r#"
 INSERT INTO accounts (user_id, account_name, account_type, account_limit)
@@ -235,21 +1345,22 @@ RETURNING account_id
     .bind(account_name)
     .bind(account_type.to_string())
     .bind(account_limit)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await?;
 
     Ok(rec.0)
 }
 
-async fn account_delete(pool: &PgPool, account_id: i64) -> Result<u64, sqlx::Error> {
+async fn account_trash(executor: impl PgExecutor<'_>, account_id: i64) -> Result<u64, sqlx::Error> {
     let rows = sqlx::query(
         r#"
-DELETE FROM accounts
-WHERE account_id=($1)
+UPDATE accounts
+SET deleted_at=NOW()
+WHERE account_id=($1) AND deleted_at IS NULL
         "#,
     )
     .bind(account_id)
-    .execute(pool)
+    .execute(executor)
     .await?
     .rows_affected();
 
@@ -258,23 +1369,28 @@ WHERE account_id=($1)
 
 // We only update account_name and account_limit
 async fn account_update(
-    pool: &PgPool,
+    executor: impl PgExecutor<'_>,
     account_id: i64,
     account_name: &str,
     account_limit: i32,
 ) -> Result<i64, sqlx::Error> {
-    sqlx::query(
+    // a trashed account can't be edited until it is restored
+    let rows = sqlx::query(
         r#"
 UPDATE accounts
 SET account_name=($1), account_limit=($2)
-WHERE account_id=($3)
+WHERE account_id=($3) AND deleted_at IS NULL
         "#,
     )
     .bind(account_name)
     .bind(account_limit)
     .bind(account_id)
-    .execute(pool)
-    .await?;
+    .execute(executor)
+    .await?
+    .rows_affected();
+    if rows == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
 
     Ok(account_id)
 }
@@ -287,7 +1403,7 @@ async fn account_get_all_for_user(
         r#"
 SELECT *
 FROM accounts
-WHERE user_id=($1)
+WHERE user_id=($1) AND deleted_at IS NULL
         "#,
     )
     .bind(user_id)
@@ -297,80 +1413,256 @@ WHERE user_id=($1)
     Ok(accounts)
 }
 
-/*****************************************************************************/
-/*                             Transaction APIs                              */
-/*****************************************************************************/
-
-async fn transaction_create(
+async fn account_get_all_with_balance_for_user(
     pool: &PgPool,
-    transaction_date: &NaiveDate,
-    transaction_type: &TransactionType,
-    category: &str,
-    amount: f64,
-    transaction_memo: &str,
-    account_id: i64,
-) -> Result<i64, sqlx::Error> {
-    let mut adjusted_amount = amount;
-    if transaction_type == &TransactionType::Expenses && amount > 0.0 {
-        // Assume user means negative
-        adjusted_amount = 0.0 - amount;
-    }
-    let rec: (i64,) = sqlx::query_as(
+    user_id: i64,
+) -> Result<Vec<AccountBalance>, sqlx::Error> {
+    let accounts: Vec<AccountBalance> = sqlx::query_as(
         r#"
-INSERT INTO transactions
-(transaction_date, transaction_type, category, amount, transaction_memo, account_id)
-VALUES ($1, $2, $3, $4, $5, $6)
-RETURNING transaction_id
+SELECT a.account_id, a.user_id, a.account_name, a.account_type, a.account_limit,
+       COALESCE(SUM(t.amount), 0) AS balance, COUNT(t.transaction_id) AS transaction_count
+FROM accounts a
+LEFT JOIN transactions t ON t.account_id=a.account_id AND t.deleted_at IS NULL
+WHERE a.user_id=($1) AND a.deleted_at IS NULL
+GROUP BY a.account_id
+ORDER BY a.account_id
         "#,
     )
-    .bind(transaction_date)
-    .bind(transaction_type.to_string())
-    .bind(category)
-    .bind(adjusted_amount)
-    .bind(transaction_memo)
-    .bind(account_id)
-    .fetch_one(pool)
+    .bind(user_id)
+    .fetch_all(pool)
     .await?;
 
-    Ok(rec.0)
+    Ok(accounts)
 }
 
-async fn transaction_delete(pool: &PgPool, transaction_id: i64) -> Result<u64, sqlx::Error> {
-    let rows = sqlx::query(
+async fn net_worth_get_series_for_user(
+    pool: &PgPool,
+    user_id: i64,
+    start_date: &NaiveDate,
+    end_date: &NaiveDate,
+    granularity: &Granularity,
+) -> Result<Vec<NetWorthPoint>, sqlx::Error> {
+    // credit balances are negative while owed, so the plain sum is the net worth.
+    // each account uses its daily snapshot, computing the balance from the
+    // transactions only for days the scheduler has no snapshot of
+    let points: Vec<NetWorthPoint> = sqlx::query_as(
         r#"
-DELETE FROM transactions
-WHERE transaction_id=($1)
+SELECT d::date AS period_date,
+       COALESCE((
+           SELECT SUM(COALESCE(
+               (
+                   SELECT s.balance
+                   FROM balance_snapshots s
+                   WHERE s.account_id=a.account_id AND s.snapshot_date=d::date
+               ),
+               (
+                   SELECT SUM(t.amount)
+                   FROM live_transactions t
+                   WHERE t.account_id=a.account_id AND t.transaction_date <= d::date
+               ),
+               0
+           ))
+           FROM accounts a
+           WHERE a.user_id=($1) AND a.deleted_at IS NULL
+       ), 0) AS net_worth
+FROM generate_series(($3)::date, ($2)::date, -(($4)::interval)) AS d
+ORDER BY period_date
         "#,
     )
-    .bind(transaction_id)
-    .execute(pool)
-    .await?
-    .rows_affected();
+    .bind(user_id)
+    .bind(start_date)
+    .bind(end_date)
+    .bind(granularity.interval())
+    .fetch_all(pool)
+    .await?;
 
-    Ok(rows)
+    Ok(points)
 }
 
-async fn transaction_update(
-    pool: &PgPool,
-    transaction_id: i64,
-    transaction_date: &NaiveDate,
-    transaction_type: &TransactionType,
-    category: &str,
-    amount: f64,
-    transaction_memo: &str,
-    account_id: i64,
-) -> Result<i64, sqlx::Error> {
-    let mut adjusted_amount = amount;
-    if transaction_type == &TransactionType::Expenses && amount > 0.0 {
+/*****************************************************************************/
+/*                             Transaction APIs                              */
+/*****************************************************************************/
+
+fn signed_amount(transaction_type: &TransactionType, amount: f64) -> f64 {
+    if transaction_type == &TransactionType::Expenses && amount > 0.0 {
         // Assume user means negative
-        adjusted_amount = 0.0 - amount;
+        0.0 - amount
+    } else {
+        amount
     }
-    sqlx::query(
+}
+
+// Validates and stores a transaction along with its tags, splits and audit
+// entry, leaving the commit to the caller
+#[allow(clippy::too_many_arguments)]
+async fn transaction_save(
+    conn: &mut PgConnection,
+    transaction_id: Option<i64>,
+    transaction_date: &NaiveDate,
+    transaction_type: &TransactionType,
+    category: &str,
+    amount: f64,
+    transaction_memo: &str,
+    account_id: i64,
+    tags: &Option<Vec<String>>,
+    splits: &Option<Vec<SplitLine>>,
+    actor: &str,
+) -> Result<i64, sqlx::Error> {
+    // splits kept from before take the sign of the new type when it changes
+    if let (None, Some(tid)) = (splits, transaction_id) {
+        transaction_split_resign(&mut *conn, tid, transaction_type).await?;
+    }
+
+    // splits must add up to the transaction amount, including the splits
+    // already stored when the amount or type changes without new splits
+    let split_total = match (splits, transaction_id) {
+        (Some(lines), _) if !lines.is_empty() => Some(
+            lines
+                .iter()
+                .map(|l| signed_amount(transaction_type, l.amount))
+                .sum::<f64>(),
+        ),
+        (None, Some(tid)) => transaction_split_get_sum(&mut *conn, tid).await?,
+        _ => None,
+    };
+    if let Some(total) = split_total {
+        let signed = signed_amount(transaction_type, amount);
+        if (total - signed).abs() >= 0.005 {
+            return Err(sqlx::Error::InvalidArgument(format!(
+                "splits add up to {:.2} but the transaction amount is {:.2}",
+                total, signed
+            )));
+        }
+    }
+
+    // store the category under its canonical spelling
+    let (category_id, category) = if category.trim().is_empty() {
+        (None, String::new())
+    } else {
+        let c = category_get_or_create_for_account(&mut *conn, account_id, category.trim()).await?;
+        (Some(c.category_id), c.category_name)
+    };
+
+    let before = match transaction_id {
+        Some(tid) => audit_snapshot(&mut *conn, AuditEntity::Transaction, tid).await?,
+        None => None,
+    };
+    // the old account and date lose their snapshots as well as the new ones
+    if let Some(tid) = transaction_id {
+        balance_snapshot_delete_for_transaction(&mut *conn, tid).await?;
+    }
+
+    let transaction_id = if let Some(tid) = transaction_id {
+        transaction_update(
+            &mut *conn,
+            tid,
+            transaction_date,
+            transaction_type,
+            category_id,
+            &category,
+            amount,
+            transaction_memo,
+            account_id,
+        )
+        .await?
+    } else {
+        transaction_create(
+            &mut *conn,
+            transaction_date,
+            transaction_type,
+            category_id,
+            &category,
+            amount,
+            transaction_memo,
+            account_id,
+        )
+        .await?
+    };
+
+    // a missing tag list leaves the existing tags untouched
+    if let Some(tags) = tags {
+        transaction_tag_set(&mut *conn, transaction_id, tags).await?;
+    }
+
+    // an empty split list turns the transaction back into a single category
+    if let Some(lines) = splits {
+        transaction_split_set(&mut *conn, transaction_id, transaction_type, lines, account_id).await?;
+    }
+
+    balance_snapshot_delete_for_transaction(&mut *conn, transaction_id).await?;
+    audit_record(&mut *conn, AuditEntity::Transaction, transaction_id, actor, before).await?;
+    Ok(transaction_id)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn transaction_create(
+    executor: impl PgExecutor<'_>,
+    transaction_date: &NaiveDate,
+    transaction_type: &TransactionType,
+    category_id: Option<i64>,
+    category: &str,
+    amount: f64,
+    transaction_memo: &str,
+    account_id: i64,
+) -> Result<i64, sqlx::Error> {
+    let adjusted_amount = signed_amount(transaction_type, amount);
+    let rec: (i64,) = sqlx::query_as(
+        r#"
+INSERT INTO transactions
+(transaction_date, transaction_type, category, amount, transaction_memo, account_id, category_id)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+RETURNING transaction_id
+        "#,
+    )
+    .bind(transaction_date)
+    .bind(transaction_type.to_string())
+    .bind(category)
+    .bind(adjusted_amount)
+    .bind(transaction_memo)
+    .bind(account_id)
+    .bind(category_id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(rec.0)
+}
+
+async fn transaction_trash(executor: impl PgExecutor<'_>, transaction_id: i64) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+UPDATE transactions
+SET deleted_at=NOW()
+WHERE transaction_id=($1) AND deleted_at IS NULL
+        "#,
+    )
+    .bind(transaction_id)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn transaction_update(
+    executor: impl PgExecutor<'_>,
+    transaction_id: i64,
+    transaction_date: &NaiveDate,
+    transaction_type: &TransactionType,
+    category_id: Option<i64>,
+    category: &str,
+    amount: f64,
+    transaction_memo: &str,
+    account_id: i64,
+) -> Result<i64, sqlx::Error> {
+    let adjusted_amount = signed_amount(transaction_type, amount);
+    // a trashed transaction can't be edited until it is restored
+    let rows = sqlx::query(
         r#"
 UPDATE transactions
 SET transaction_date=($1), transaction_type=($2), category=($3), amount=($4),
-    transaction_memo=($5), account_id=($6)
-WHERE transaction_id=($7)
+    transaction_memo=($5), account_id=($6), category_id=($7)
+WHERE transaction_id=($8) AND deleted_at IS NULL
         "#,
     )
     .bind(transaction_date)
@@ -379,32 +1671,82 @@ WHERE transaction_id=($7)
     .bind(adjusted_amount)
     .bind(transaction_memo)
     .bind(account_id)
+    .bind(category_id)
     .bind(transaction_id)
-    .execute(pool)
-    .await?;
+    .execute(executor)
+    .await?
+    .rows_affected();
+    if rows == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
 
     Ok(transaction_id)
 }
 
+async fn transaction_get_balance_on_date(
+    pool: &PgPool,
+    account_id: i64,
+    date: &NaiveDate,
+) -> Result<f64, sqlx::Error> {
+    let sum: (Option<f64>,) = sqlx::query_as(
+        r#"
+SELECT SUM(amount)
+FROM live_transactions
+WHERE account_id=($1) AND transaction_date <= ($2)
+        "#,
+    )
+    .bind(account_id)
+    .bind(date)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(sum.0.unwrap_or(0.0))
+}
+
 async fn transaction_get_all_for_account(
     pool: &PgPool,
     account_id: i64,
     transaction_type: &Option<TransactionType>,
     category: &Option<String>,
+    tags: &[String],
+    tag_match: &TagMatch,
 ) -> Result<Vec<Transaction>, sqlx::Error> {
     let mut query: QueryBuilder<'_, sqlx::Postgres> =
-        QueryBuilder::new("SELECT * FROM transactions WHERE account_id=");
+        QueryBuilder::new(
+            r#"
+SELECT t.transaction_id, t.transaction_date, t.transaction_type,
+       COALESCE(c.category_name, t.category, '') AS category,
+       t.amount, t.transaction_memo, t.account_id, t.category_id,
+       ARRAY(
+           SELECT g.tag_name
+           FROM transaction_tags tt
+           JOIN tags g ON g.tag_id=tt.tag_id
+           WHERE tt.transaction_id=t.transaction_id
+           ORDER BY g.tag_name
+       ) AS tags,
+       (
+           SELECT COUNT(*)
+           FROM attachments f
+           WHERE f.transaction_id=t.transaction_id
+       ) AS attachment_count,
+       b.balance
+FROM transactions t
+JOIN (
+    SELECT transaction_id,
+           SUM(amount) OVER (ORDER BY transaction_date, transaction_id) AS balance
+    FROM live_transactions
+    WHERE account_id="#
+        );
     query.push_bind(account_id);
-
-    if let Some(trans_type) = transaction_type {
-        query.push(" AND transaction_type=");
-        query.push_bind(trans_type.to_string());
-    }
-
-    if let Some(trans_category) = category {
-        query.push(" AND category=");
-        query.push_bind(trans_category);
-    }
+    query.push(
+        r#"
+) b ON b.transaction_id=t.transaction_id
+LEFT JOIN categories c ON c.category_id=t.category_id
+WHERE t.transaction_id IN (
+    SELECT t.transaction_id FROM transaction_lines t WHERE t.account_id="#
+    );
+    push_transaction_filters(&mut query, account_id, transaction_type, category, tags, tag_match);
+    query.push(") ORDER BY t.transaction_date, t.transaction_id");
 
     let transactions: Vec<Transaction> = query.build_query_as()
         .fetch_all(pool)
@@ -418,30 +1760,1575 @@ async fn transaction_get_sum_for_account(
     account_id: i64,
     transaction_type: &Option<TransactionType>,
     category: &Option<String>,
+    tags: &[String],
+    tag_match: &TagMatch,
 ) -> Result<f64, sqlx::Error> {
     let mut query: QueryBuilder<'_, sqlx::Postgres> =
         QueryBuilder::new(
-            "SELECT SUM(amount) FROM transactions WHERE account_id="
+            "SELECT SUM(t.amount) FROM transaction_lines t WHERE t.account_id="
         );
+    push_transaction_filters(&mut query, account_id, transaction_type, category, tags, tag_match);
+
+    let sum: (Option<f64>,) = query.build_query_as()
+        .fetch_one(pool)
+        .await?;
+    
+    if let Some(s) = sum.0 {
+        Ok(s)
+    } else {
+        Ok(0.0)
+    }
+}
+
+// Appends the account id and the optional filters to a query over transaction
+// lines aliased as "t", so a split transaction matches a category through any
+// of its splits. A category filter matches the category case-insensitively
+// together with all of its sub-categories.
+fn push_transaction_filters(
+    query: &mut QueryBuilder<'_, sqlx::Postgres>,
+    account_id: i64,
+    transaction_type: &Option<TransactionType>,
+    category: &Option<String>,
+    tags: &[String],
+    tag_match: &TagMatch,
+) {
     query.push_bind(account_id);
 
     if let Some(trans_type) = transaction_type {
-        query.push(" AND transaction_type=");
+        query.push(" AND t.transaction_type=");
         query.push_bind(trans_type.to_string());
     }
 
     if let Some(trans_category) = category {
-        query.push(" AND category=");
-        query.push_bind(trans_category);
+        query.push(
+            r#"
+ AND t.category_id IN (
+    WITH RECURSIVE subtree AS (
+        SELECT c.category_id
+        FROM categories c
+        JOIN accounts a ON a.user_id=c.user_id
+        WHERE a.account_id="#
+        );
+        query.push_bind(account_id);
+        query.push(" AND LOWER(c.category_name)=LOWER(");
+        query.push_bind(trans_category.trim().to_string());
+        query.push(
+            r#")
+        UNION
+        SELECT c.category_id
+        FROM categories c
+        JOIN subtree s ON c.parent_id=s.category_id
+    )
+    SELECT category_id FROM subtree
+)"#
+        );
     }
 
-    let sum: (Option<f64>,) = query.build_query_as()
-        .fetch_one(pool)
+    push_tag_filter(query, tags, tag_match);
+}
+
+// Appends a filter keeping transactions aliased as "t" that carry any or all
+// of the given tags. An empty tag list keeps every transaction.
+fn push_tag_filter(
+    query: &mut QueryBuilder<'_, sqlx::Postgres>,
+    tags: &[String],
+    tag_match: &TagMatch,
+) {
+    if tags.is_empty() {
+        return;
+    }
+
+    query.push(
+        r#"
+ AND (
+    SELECT COUNT(*)
+    FROM transaction_tags tt
+    JOIN tags g ON g.tag_id=tt.tag_id
+    WHERE tt.transaction_id=t.transaction_id AND g.tag_name=ANY("#
+    );
+    query.push_bind(tags.to_vec());
+    match tag_match {
+        TagMatch::Any => query.push(")) > 0"),
+        TagMatch::All => query.push(")) = ").push_bind(tags.len() as i64),
+    };
+}
+
+/*****************************************************************************/
+/*                                 Tag APIs                                  */
+/*****************************************************************************/
+
+// Tags belong to the owner of the transaction's account and are created on
+// first use
+async fn transaction_tag_add(
+    executor: impl PgExecutor<'_>,
+    transaction_id: i64,
+    tag_name: &str,
+) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+WITH owner AS (
+    SELECT a.user_id
+    FROM transactions t
+    JOIN accounts a ON a.account_id=t.account_id
+    WHERE t.transaction_id=($1)
+), new_tag AS (
+    INSERT INTO tags (user_id, tag_name)
+    SELECT user_id, ($2) FROM owner
+    ON CONFLICT (user_id, tag_name) DO NOTHING
+    RETURNING tag_id
+), tag AS (
+    SELECT tag_id FROM new_tag
+    UNION ALL
+    SELECT g.tag_id FROM tags g JOIN owner o ON o.user_id=g.user_id WHERE g.tag_name=($2)
+)
+INSERT INTO transaction_tags (transaction_id, tag_id)
+SELECT ($1), tag_id FROM tag LIMIT 1
+ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(transaction_id)
+    .bind(tag_name)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+async fn transaction_tag_exists(
+    executor: impl PgExecutor<'_>,
+    transaction_id: i64,
+    tag_name: &str,
+) -> Result<bool, sqlx::Error> {
+    let rec: (bool,) = sqlx::query_as(
+        r#"
+SELECT EXISTS (
+    SELECT 1
+    FROM transaction_tags tt
+    JOIN tags g ON g.tag_id=tt.tag_id
+    WHERE tt.transaction_id=($1) AND g.tag_name=($2)
+)
+        "#,
+    )
+    .bind(transaction_id)
+    .bind(tag_name)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(rec.0)
+}
+
+async fn transaction_tag_remove(
+    executor: impl PgExecutor<'_>,
+    transaction_id: i64,
+    tag_name: &str,
+) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+DELETE FROM transaction_tags tt
+USING tags g
+WHERE tt.tag_id=g.tag_id AND tt.transaction_id=($1) AND g.tag_name=($2)
+        "#,
+    )
+    .bind(transaction_id)
+    .bind(tag_name)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+// Replaces all tags of a transaction with the given ones
+async fn transaction_tag_set(
+    conn: &mut PgConnection,
+    transaction_id: i64,
+    tags: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+DELETE FROM transaction_tags
+WHERE transaction_id=($1)
+        "#,
+    )
+    .bind(transaction_id)
+    .execute(&mut *conn)
+    .await?;
+
+    for tag in tags {
+        transaction_tag_add(&mut *conn, transaction_id, tag).await?;
+    }
+
+    Ok(())
+}
+
+/*****************************************************************************/
+/*                               Split APIs                                  */
+/*****************************************************************************/
+
+// Replaces all splits of a transaction, resolving their categories like the
+// category of the transaction itself
+async fn transaction_split_set(
+    conn: &mut PgConnection,
+    transaction_id: i64,
+    transaction_type: &TransactionType,
+    lines: &[SplitLine],
+    account_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+DELETE FROM transaction_splits
+WHERE transaction_id=($1)
+        "#,
+    )
+    .bind(transaction_id)
+    .execute(&mut *conn)
+    .await?;
+
+    for line in lines {
+        let (category_id, category) = if line.category.trim().is_empty() {
+            (None, String::new())
+        } else {
+            let c = category_get_or_create_for_account(&mut *conn, account_id, line.category.trim()).await?;
+            (Some(c.category_id), c.category_name)
+        };
+        sqlx::query(
+            r#"
+INSERT INTO transaction_splits (transaction_id, category_id, category, amount, split_memo)
+VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(transaction_id)
+        .bind(category_id)
+        .bind(category)
+        .bind(signed_amount(transaction_type, line.amount))
+        .bind(&line.split_memo)
+        .execute(&mut *conn)
         .await?;
-    
-    if let Some(s) = sum.0 {
-        Ok(s)
-    } else {
-        Ok(0.0)
     }
+
+    Ok(())
+}
+
+// Flips the sign of the splits of a transaction whose type is being changed,
+// the same way signed_amount does for new splits
+async fn transaction_split_resign(
+    executor: impl PgExecutor<'_>,
+    transaction_id: i64,
+    transaction_type: &TransactionType,
+) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+UPDATE transaction_splits s
+SET amount=CASE WHEN ($2)='Expenses' THEN -ABS(s.amount) ELSE ABS(s.amount) END
+FROM transactions t
+WHERE t.transaction_id=s.transaction_id AND s.transaction_id=($1) AND t.transaction_type<>($2)
+        "#,
+    )
+    .bind(transaction_id)
+    .bind(transaction_type.to_string())
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+// Sum of the splits of a transaction, none when it is not split
+async fn transaction_split_get_sum(
+    executor: impl PgExecutor<'_>,
+    transaction_id: i64,
+) -> Result<Option<f64>, sqlx::Error> {
+    let sum: (Option<f64>,) = sqlx::query_as(
+        r#"
+SELECT SUM(amount)
+FROM transaction_splits
+WHERE transaction_id=($1)
+        "#,
+    )
+    .bind(transaction_id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(sum.0)
+}
+
+async fn transaction_split_get_for_transactions(
+    pool: &PgPool,
+    transaction_ids: &[i64],
+) -> Result<Vec<TransactionSplit>, sqlx::Error> {
+    let splits: Vec<TransactionSplit> = sqlx::query_as(
+        r#"
+SELECT s.split_id, s.transaction_id, s.category_id,
+       COALESCE(c.category_name, s.category) AS category,
+       s.amount, s.split_memo
+FROM transaction_splits s
+LEFT JOIN categories c ON c.category_id=s.category_id
+WHERE s.transaction_id=ANY($1)
+ORDER BY s.transaction_id, s.split_id
+        "#,
+    )
+    .bind(transaction_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(splits)
+}
+
+// Categories a transaction counts towards, through its splits if it has any
+async fn transaction_line_get_category_ids(
+    pool: &PgPool,
+    transaction_id: i64,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let ids: Vec<(i64,)> = sqlx::query_as(
+        r#"
+SELECT DISTINCT category_id
+FROM transaction_lines
+WHERE transaction_id=($1) AND category_id IS NOT NULL
+        "#,
+    )
+    .bind(transaction_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ids.into_iter().map(|id| id.0).collect())
+}
+
+/*****************************************************************************/
+/*                             Attachment APIs                               */
+/*****************************************************************************/
+
+async fn attachment_create(
+    executor: impl PgExecutor<'_>,
+    transaction_id: i64,
+    file_name: &str,
+    content_type: &str,
+    content: &[u8],
+) -> Result<i64, sqlx::Error> {
+    // selecting from live_transactions turns a missing or trashed transaction
+    // into RowNotFound
+    let rec: (i64,) = sqlx::query_as(
+        r#"
+INSERT INTO attachments (transaction_id, file_name, content_type, file_size, content)
+SELECT t.transaction_id, ($2), ($3), ($4), ($5)
+FROM live_transactions t
+WHERE t.transaction_id=($1)
+RETURNING attachment_id
+        "#,
+    )
+    .bind(transaction_id)
+    .bind(file_name)
+    .bind(content_type)
+    .bind(content.len() as i64)
+    .bind(content)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(rec.0)
+}
+
+async fn attachment_get_all_for_transaction(
+    pool: &PgPool,
+    transaction_id: i64,
+) -> Result<Vec<Attachment>, sqlx::Error> {
+    let attachments: Vec<Attachment> = sqlx::query_as(
+        r#"
+SELECT attachment_id, transaction_id, file_name, content_type, file_size, uploaded_at
+FROM attachments
+WHERE transaction_id=($1)
+ORDER BY attachment_id
+        "#,
+    )
+    .bind(transaction_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(attachments)
+}
+
+async fn attachment_get_content(
+    pool: &PgPool,
+    attachment_id: i64,
+) -> Result<AttachmentContent, sqlx::Error> {
+    let attachment: AttachmentContent = sqlx::query_as(
+        r#"
+SELECT file_name, content_type, content
+FROM attachments
+WHERE attachment_id=($1)
+        "#,
+    )
+    .bind(attachment_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(attachment)
+}
+
+async fn attachment_delete(executor: impl PgExecutor<'_>, attachment_id: i64) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+DELETE FROM attachments
+WHERE attachment_id=($1)
+        "#,
+    )
+    .bind(attachment_id)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+/*****************************************************************************/
+/*                              Category APIs                                */
+/*****************************************************************************/
+
+async fn category_create(
+    executor: impl PgExecutor<'_>,
+    user_id: i64,
+    category_name: &str,
+    parent_id: Option<i64>,
+) -> Result<i64, sqlx::Error> {
+    let rec: (i64,) = sqlx::query_as(
+        r#"
+INSERT INTO categories (user_id, category_name, parent_id)
+VALUES ($1, $2, $3)
+RETURNING category_id
+        "#,
+    )
+    .bind(user_id)
+    .bind(category_name)
+    .bind(parent_id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(rec.0)
+}
+
+async fn category_update(
+    executor: impl PgExecutor<'_>,
+    category_id: i64,
+    category_name: &str,
+    parent_id: Option<i64>,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query(
+        r#"
+UPDATE categories
+SET category_name=($1), parent_id=($2)
+WHERE category_id=($3)
+        "#,
+    )
+    .bind(category_name)
+    .bind(parent_id)
+    .bind(category_id)
+    .execute(executor)
+    .await?;
+
+    Ok(category_id)
+}
+
+// Sub-categories are moved up to the top level by the ON DELETE SET NULL
+// constraint, and transactions keep their legacy category text
+async fn category_delete(executor: impl PgExecutor<'_>, category_id: i64) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+DELETE FROM categories
+WHERE category_id=($1)
+        "#,
+    )
+    .bind(category_id)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+async fn category_get_by_name_for_user(
+    pool: &PgPool,
+    user_id: i64,
+    category_name: &str,
+) -> Result<Category, sqlx::Error> {
+    let category: Option<Category> = sqlx::query_as(
+        r#"
+SELECT *
+FROM categories
+WHERE user_id=($1) AND LOWER(category_name)=LOWER($2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(category_name)
+    .fetch_optional(pool)
+    .await?;
+
+    category.ok_or_else(|| sqlx::Error::InvalidArgument(format!("unknown category {}", category_name)))
+}
+
+async fn category_get_one(pool: &PgPool, category_id: i64) -> Result<Category, sqlx::Error> {
+    let category: Category = sqlx::query_as(
+        r#"
+SELECT *
+FROM categories
+WHERE category_id=($1)
+        "#,
+    )
+    .bind(category_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(category)
+}
+
+async fn category_get_all_for_user(
+    pool: &PgPool,
+    user_id: i64,
+) -> Result<Vec<Category>, sqlx::Error> {
+    let categories: Vec<Category> = sqlx::query_as(
+        r#"
+SELECT *
+FROM categories
+WHERE user_id=($1)
+ORDER BY category_name
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(categories)
+}
+
+// A use counts fully on the day it happens and half as much a month later,
+// so categories used often recently come before ones used often long ago
+async fn category_get_usage_for_user(
+    pool: &PgPool,
+    user_id: i64,
+) -> Result<Vec<CategoryUsage>, sqlx::Error> {
+    let usage: Vec<CategoryUsage> = sqlx::query_as(
+        r#"
+SELECT c.category_name,
+       COUNT(t.transaction_id) AS use_count,
+       MAX(t.transaction_date) AS last_used,
+       COALESCE(SUM(1.0 / (1 + GREATEST(CURRENT_DATE - t.transaction_date, 0) / 30.0)), 0)::DOUBLE PRECISION AS score
+FROM categories c
+LEFT JOIN transaction_lines t ON t.category_id=c.category_id
+WHERE c.user_id=($1)
+GROUP BY c.category_id, c.category_name
+ORDER BY score DESC, c.category_name
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(usage)
+}
+
+// Ids of the category and all of its descendants
+async fn category_get_subtree_ids(pool: &PgPool, category_id: i64) -> Result<Vec<i64>, sqlx::Error> {
+    let ids: Vec<(i64,)> = sqlx::query_as(
+        r#"
+WITH RECURSIVE subtree AS (
+    SELECT category_id FROM categories WHERE category_id=($1)
+    UNION
+    SELECT c.category_id FROM categories c JOIN subtree s ON c.parent_id=s.category_id
+)
+SELECT category_id FROM subtree
+        "#,
+    )
+    .bind(category_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ids.into_iter().map(|r| r.0).collect())
+}
+
+// Ids of the category and all of its parents up to the top level
+async fn category_get_ancestor_ids(pool: &PgPool, category_id: i64) -> Result<Vec<i64>, sqlx::Error> {
+    let ids: Vec<(i64,)> = sqlx::query_as(
+        r#"
+WITH RECURSIVE ancestors AS (
+    SELECT category_id, parent_id FROM categories WHERE category_id=($1)
+    UNION
+    SELECT c.category_id, c.parent_id FROM categories c JOIN ancestors a ON c.category_id=a.parent_id
+)
+SELECT category_id FROM ancestors
+        "#,
+    )
+    .bind(category_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ids.into_iter().map(|r| r.0).collect())
+}
+
+// Looks up the category of the account owner by name, ignoring case, and
+// creates it as a top-level category if it does not exist yet
+async fn category_get_or_create_for_account(
+    executor: impl PgExecutor<'_>,
+    account_id: i64,
+    category_name: &str,
+) -> Result<Category, sqlx::Error> {
+    let category: Category = sqlx::query_as(
+        r#"
+WITH owner AS (
+    SELECT user_id FROM accounts WHERE account_id=($1)
+), inserted AS (
+    INSERT INTO categories (user_id, category_name)
+    SELECT user_id, ($2) FROM owner
+    ON CONFLICT (user_id, (LOWER(category_name))) DO NOTHING
+    RETURNING *
+)
+SELECT * FROM inserted
+UNION ALL
+SELECT c.*
+FROM categories c
+JOIN owner o ON o.user_id=c.user_id
+WHERE LOWER(c.category_name)=LOWER($2)
+LIMIT 1
+        "#,
+    )
+    .bind(account_id)
+    .bind(category_name)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(category)
+}
+
+async fn category_seed_defaults(conn: &mut PgConnection, user_id: i64) -> Result<(), sqlx::Error> {
+    for (name, parent) in DEFAULT_CATEGORIES.iter() {
+        sqlx::query(
+            r#"
+INSERT INTO categories (user_id, category_name, parent_id)
+VALUES ($1, $2, (SELECT category_id FROM categories WHERE user_id=($1) AND category_name=($3)))
+ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(parent)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn category_get_totals_for_account(
+    pool: &PgPool,
+    account_id: i64,
+    tags: &[String],
+    tag_match: &TagMatch,
+) -> Result<Vec<CategoryTotal>, sqlx::Error> {
+    let mut query: QueryBuilder<'_, sqlx::Postgres> = QueryBuilder::new(
+        r#"
+WITH RECURSIVE tree AS (
+    SELECT c.category_id AS ancestor_id, c.category_id AS descendant_id
+    FROM categories c
+    JOIN accounts a ON a.user_id=c.user_id
+    WHERE a.account_id="#,
+    );
+    query.push_bind(account_id);
+    query.push(
+        r#"
+    UNION
+    SELECT tree.ancestor_id, c.category_id
+    FROM tree
+    JOIN categories c ON c.parent_id=tree.descendant_id
+)
+SELECT c.category_id, c.category_name, c.parent_id, COALESCE(SUM(t.amount), 0) AS total
+FROM categories c
+JOIN tree ON tree.ancestor_id=c.category_id
+LEFT JOIN transaction_lines t ON t.category_id=tree.descendant_id AND t.account_id="#,
+    );
+    query.push_bind(account_id);
+    push_tag_filter(&mut query, tags, tag_match);
+    query.push(
+        r#"
+GROUP BY c.category_id
+ORDER BY c.category_name
+        "#,
+    );
+
+    let totals: Vec<CategoryTotal> = query.build_query_as()
+        .fetch_all(pool)
+        .await?;
+
+    Ok(totals)
+}
+
+/*****************************************************************************/
+/*                                Chart APIs                                 */
+/*****************************************************************************/
+
+// Transaction type, category, tags and tag match, as taken by
+// push_transaction_filters
+type ChartFilters<'a> = (&'a Option<TransactionType>, &'a Option<String>, &'a [String], &'a TagMatch);
+
+// Appends the account, the filters and the date range to a query over
+// transaction lines aliased as "t"
+fn push_chart_filters(
+    query: &mut QueryBuilder<'_, sqlx::Postgres>,
+    account_id: i64,
+    (transaction_type, category, tags, tag_match): ChartFilters<'_>,
+    start_date: &Option<NaiveDate>,
+    end_date: &NaiveDate,
+) {
+    push_transaction_filters(query, account_id, transaction_type, category, tags, tag_match);
+    if let Some(start_date) = start_date {
+        query.push(" AND t.transaction_date >= ");
+        query.push_bind(*start_date);
+    }
+    query.push(" AND t.transaction_date <= ");
+    query.push_bind(*end_date);
+}
+
+// Lines without a category are counted as "Uncategorized"
+async fn chart_get_spending_by_category(
+    pool: &PgPool,
+    account_id: i64,
+    filters: ChartFilters<'_>,
+    start_date: &Option<NaiveDate>,
+    end_date: &NaiveDate,
+) -> Result<Vec<CategorySpending>, sqlx::Error> {
+    let mut query: QueryBuilder<'_, sqlx::Postgres> = QueryBuilder::new(
+        r#"
+SELECT COALESCE(c.category_name, 'Uncategorized') AS category_name, -SUM(t.amount) AS spent
+FROM transaction_lines t
+LEFT JOIN categories c ON c.category_id=t.category_id
+WHERE t.transaction_type='Expenses' AND t.account_id="#,
+    );
+    push_chart_filters(&mut query, account_id, filters, start_date, end_date);
+    query.push(
+        r#"
+GROUP BY 1
+ORDER BY spent DESC, category_name
+        "#,
+    );
+
+    let spending: Vec<CategorySpending> = query.build_query_as()
+        .fetch_all(pool)
+        .await?;
+
+    Ok(spending)
+}
+
+async fn chart_get_monthly_flow(
+    pool: &PgPool,
+    account_id: i64,
+    filters: ChartFilters<'_>,
+    start_date: &Option<NaiveDate>,
+    end_date: &NaiveDate,
+) -> Result<Vec<MonthlyFlow>, sqlx::Error> {
+    let mut query: QueryBuilder<'_, sqlx::Postgres> = QueryBuilder::new(
+        r#"
+SELECT DATE_TRUNC('month', t.transaction_date)::date AS month,
+       COALESCE(SUM(t.amount) FILTER (WHERE t.transaction_type='Income'), 0) AS income,
+       COALESCE(-SUM(t.amount) FILTER (WHERE t.transaction_type='Expenses'), 0) AS expenses
+FROM transaction_lines t
+WHERE t.account_id="#,
+    );
+    push_chart_filters(&mut query, account_id, filters, start_date, end_date);
+    query.push(
+        r#"
+GROUP BY 1
+ORDER BY 1
+        "#,
+    );
+
+    let flows: Vec<MonthlyFlow> = query.build_query_as()
+        .fetch_all(pool)
+        .await?;
+
+    Ok(flows)
+}
+
+// Balance at the end of every day with a matching transaction in the range
+async fn chart_get_balance_series(
+    pool: &PgPool,
+    account_id: i64,
+    filters: ChartFilters<'_>,
+    start_date: &Option<NaiveDate>,
+    end_date: &NaiveDate,
+) -> Result<Vec<BalancePoint>, sqlx::Error> {
+    let mut query: QueryBuilder<'_, sqlx::Postgres> = QueryBuilder::new(
+        r#"
+SELECT balance_date, balance
+FROM (
+    SELECT t.transaction_date AS balance_date,
+           SUM(SUM(t.amount)) OVER (ORDER BY t.transaction_date) AS balance
+    FROM transaction_lines t
+    WHERE t.account_id="#,
+    );
+    // the running sum starts at the first transaction, the range applies after
+    push_chart_filters(&mut query, account_id, filters, &None, end_date);
+    query.push(
+        r#"
+    GROUP BY t.transaction_date
+) b"#,
+    );
+    if let Some(start_date) = start_date {
+        query.push(" WHERE balance_date >= ");
+        query.push_bind(*start_date);
+    }
+    query.push(" ORDER BY balance_date");
+
+    let points: Vec<BalancePoint> = query.build_query_as()
+        .fetch_all(pool)
+        .await?;
+
+    Ok(points)
+}
+
+// Income and expenses over every account of the user from start_date up to,
+// but not including, end_date
+async fn chart_get_monthly_flow_for_user(
+    pool: &PgPool,
+    user_id: i64,
+    start_date: &NaiveDate,
+    end_date: &NaiveDate,
+) -> Result<MonthlyFlow, sqlx::Error> {
+    let flow: MonthlyFlow = sqlx::query_as(
+        r#"
+SELECT ($2)::date AS month,
+       COALESCE(SUM(t.amount) FILTER (WHERE t.transaction_type='Income'), 0) AS income,
+       COALESCE(-SUM(t.amount) FILTER (WHERE t.transaction_type='Expenses'), 0) AS expenses
+FROM transaction_lines t
+JOIN accounts a ON a.account_id=t.account_id
+WHERE a.user_id=($1) AND a.deleted_at IS NULL
+  AND t.transaction_date >= ($2) AND t.transaction_date < ($3)
+        "#,
+    )
+    .bind(user_id)
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(flow)
+}
+
+async fn chart_get_top_categories_for_user(
+    pool: &PgPool,
+    user_id: i64,
+    start_date: &NaiveDate,
+    end_date: &NaiveDate,
+    limit: i64,
+) -> Result<Vec<CategorySpending>, sqlx::Error> {
+    let spending: Vec<CategorySpending> = sqlx::query_as(
+        r#"
+SELECT COALESCE(c.category_name, 'Uncategorized') AS category_name, -SUM(t.amount) AS spent
+FROM transaction_lines t
+JOIN accounts a ON a.account_id=t.account_id
+LEFT JOIN categories c ON c.category_id=t.category_id
+WHERE a.user_id=($1) AND a.deleted_at IS NULL AND t.transaction_type='Expenses'
+  AND t.transaction_date >= ($2) AND t.transaction_date < ($3)
+GROUP BY 1
+ORDER BY spent DESC, category_name
+LIMIT ($4)
+        "#,
+    )
+    .bind(user_id)
+    .bind(start_date)
+    .bind(end_date)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(spending)
+}
+
+/*****************************************************************************/
+/*                               Budget APIs                                 */
+/*****************************************************************************/
+
+// First day of the month containing the date and first day of the next month
+fn month_bounds(date: &NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = date.with_day(1).unwrap();
+    (start, start + Months::new(1))
+}
+
+async fn budget_create(
+    executor: impl PgExecutor<'_>,
+    user_id: i64,
+    category_id: i64,
+    account_id: Option<i64>,
+    budget_amount: f64,
+) -> Result<i64, sqlx::Error> {
+    let rec: (i64,) = sqlx::query_as(
+        r#"
+INSERT INTO budgets (user_id, category_id, account_id, budget_amount)
+VALUES ($1, $2, $3, $4)
+RETURNING budget_id
+        "#,
+    )
+    .bind(user_id)
+    .bind(category_id)
+    .bind(account_id)
+    .bind(budget_amount)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(rec.0)
+}
+
+async fn budget_update(
+    executor: impl PgExecutor<'_>,
+    budget_id: i64,
+    category_id: i64,
+    account_id: Option<i64>,
+    budget_amount: f64,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query(
+        r#"
+UPDATE budgets
+SET category_id=($1), account_id=($2), budget_amount=($3)
+WHERE budget_id=($4)
+        "#,
+    )
+    .bind(category_id)
+    .bind(account_id)
+    .bind(budget_amount)
+    .bind(budget_id)
+    .execute(executor)
+    .await?;
+
+    Ok(budget_id)
+}
+
+async fn budget_delete(executor: impl PgExecutor<'_>, budget_id: i64) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+DELETE FROM budgets
+WHERE budget_id=($1)
+        "#,
+    )
+    .bind(budget_id)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+// Spending of each budget counts the expenses of its category and all
+// sub-categories within the month
+async fn budget_get_status_for_user(
+    pool: &PgPool,
+    user_id: i64,
+    month: &NaiveDate,
+) -> Result<Vec<BudgetStatus>, sqlx::Error> {
+    let (start, end) = month_bounds(month);
+    let budgets: Vec<BudgetStatus> = sqlx::query_as(
+        r#"
+WITH RECURSIVE tree AS (
+    SELECT category_id AS ancestor_id, category_id AS descendant_id
+    FROM categories
+    WHERE user_id=($1)
+    UNION
+    SELECT tree.ancestor_id, c.category_id
+    FROM tree
+    JOIN categories c ON c.parent_id=tree.descendant_id
+)
+SELECT b.budget_id, b.category_id, c.category_name, b.account_id,
+       b.budget_amount AS budgeted,
+       COALESCE(-SUM(t.amount), 0) AS actual,
+       b.budget_amount - COALESCE(-SUM(t.amount), 0) AS remaining
+FROM budgets b
+JOIN categories c ON c.category_id=b.category_id
+JOIN tree ON tree.ancestor_id=b.category_id
+LEFT JOIN transaction_lines t ON t.category_id=tree.descendant_id
+    AND t.transaction_type='Expenses'
+    AND t.transaction_date >= ($2) AND t.transaction_date < ($3)
+    AND (b.account_id IS NULL OR t.account_id=b.account_id)
+WHERE b.user_id=($1)
+GROUP BY b.budget_id, c.category_name
+ORDER BY c.category_name, b.budget_id
+        "#,
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(budgets)
+}
+
+// Messages for every budget covering the expense that is now overspent
+async fn budget_get_warnings(
+    pool: &PgPool,
+    account_id: i64,
+    category_ids: &[i64],
+    transaction_date: &NaiveDate,
+) -> Result<Vec<String>, sqlx::Error> {
+    let Some(first_id) = category_ids.first() else {
+        return Ok(Vec::new());
+    };
+    let category = category_get_one(pool, *first_id).await?;
+    let mut ancestors = Vec::new();
+    for category_id in category_ids {
+        ancestors.extend(category_get_ancestor_ids(pool, *category_id).await?);
+    }
+    let budgets = budget_get_status_for_user(pool, category.user_id, transaction_date).await?;
+
+    Ok(budgets
+        .iter()
+        .filter(|b| ancestors.contains(&b.category_id))
+        .filter(|b| b.account_id.is_none() || b.account_id == Some(account_id))
+        .filter(|b| b.actual > b.budgeted)
+        .map(|b| {
+            format!(
+                "Over budget for {}: spent {:.2} of {:.2} in {}",
+                b.category_name,
+                b.actual,
+                b.budgeted,
+                transaction_date.format("%Y-%m"),
+            )
+        })
+        .collect())
+}
+
+/*****************************************************************************/
+/*                         Recurring Transaction APIs                        */
+/*****************************************************************************/
+
+#[allow(clippy::too_many_arguments)]
+async fn recurring_create(
+    executor: impl PgExecutor<'_>,
+    account_id: i64,
+    transaction_type: &TransactionType,
+    category: &str,
+    amount: f64,
+    transaction_memo: &str,
+    frequency: &Frequency,
+    start_date: &NaiveDate,
+    end_date: &Option<NaiveDate>,
+) -> Result<i64, sqlx::Error> {
+    let rec: (i64,) = sqlx::query_as(
+        r#"
+INSERT INTO recurring_transactions
+(account_id, transaction_type, category, amount, transaction_memo, frequency,
+ start_date, end_date, next_date)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $7)
+RETURNING recurring_id
+        "#,
+    )
+    .bind(account_id)
+    .bind(transaction_type.to_string())
+    .bind(category)
+    .bind(amount)
+    .bind(transaction_memo)
+    .bind(frequency.to_string())
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(rec.0)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn recurring_update(
+    executor: impl PgExecutor<'_>,
+    recurring_id: i64,
+    account_id: i64,
+    transaction_type: &TransactionType,
+    category: &str,
+    amount: f64,
+    transaction_memo: &str,
+    frequency: &Frequency,
+    start_date: &NaiveDate,
+    end_date: &Option<NaiveDate>,
+    next_date: &NaiveDate,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query(
+        r#"
+UPDATE recurring_transactions
+SET account_id=($1), transaction_type=($2), category=($3), amount=($4),
+    transaction_memo=($5), frequency=($6), start_date=($7), end_date=($8),
+    next_date=($9)
+WHERE recurring_id=($10)
+        "#,
+    )
+    .bind(account_id)
+    .bind(transaction_type.to_string())
+    .bind(category)
+    .bind(amount)
+    .bind(transaction_memo)
+    .bind(frequency.to_string())
+    .bind(start_date)
+    .bind(end_date)
+    .bind(next_date)
+    .bind(recurring_id)
+    .execute(executor)
+    .await?;
+
+    Ok(recurring_id)
+}
+
+async fn recurring_set_paused(
+    executor: impl PgExecutor<'_>,
+    recurring_id: i64,
+    paused: bool,
+    next_date: &NaiveDate,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+UPDATE recurring_transactions
+SET paused=($1), next_date=($2)
+WHERE recurring_id=($3)
+        "#,
+    )
+    .bind(paused)
+    .bind(next_date)
+    .bind(recurring_id)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+// Moves next_date from the given occurrence to the following one, returning 0
+// if next_date was already moved by someone else
+async fn recurring_advance(
+    executor: impl PgExecutor<'_>,
+    recurring_id: i64,
+    occurrence: &NaiveDate,
+    following: &NaiveDate,
+) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+UPDATE recurring_transactions
+SET next_date=($1)
+WHERE recurring_id=($2) AND next_date=($3)
+        "#,
+    )
+    .bind(following)
+    .bind(recurring_id)
+    .bind(occurrence)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+async fn recurring_delete(executor: impl PgExecutor<'_>, recurring_id: i64) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+DELETE FROM recurring_transactions
+WHERE recurring_id=($1)
+        "#,
+    )
+    .bind(recurring_id)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+async fn recurring_get_one(
+    executor: impl PgExecutor<'_>,
+    recurring_id: i64,
+) -> Result<RecurringTransaction, sqlx::Error> {
+    let recurring: RecurringTransaction = sqlx::query_as(
+        r#"
+SELECT r.*, a.account_name
+FROM recurring_transactions r
+JOIN accounts a ON a.account_id=r.account_id
+WHERE r.recurring_id=($1)
+        "#,
+    )
+    .bind(recurring_id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(recurring)
+}
+
+async fn recurring_get_all_for_user(
+    pool: &PgPool,
+    user_id: i64,
+) -> Result<Vec<RecurringTransaction>, sqlx::Error> {
+    let recurring: Vec<RecurringTransaction> = sqlx::query_as(
+        r#"
+SELECT r.*, a.account_name
+FROM recurring_transactions r
+JOIN accounts a ON a.account_id=r.account_id
+WHERE a.user_id=($1) AND a.deleted_at IS NULL
+ORDER BY r.next_date, r.recurring_id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(recurring)
+}
+
+// Schedules that are not paused with an occurrence due by the given date
+async fn recurring_get_upcoming_for_user(
+    pool: &PgPool,
+    user_id: i64,
+    until: &NaiveDate,
+) -> Result<Vec<RecurringTransaction>, sqlx::Error> {
+    let recurring: Vec<RecurringTransaction> = sqlx::query_as(
+        r#"
+SELECT r.*, a.account_name
+FROM recurring_transactions r
+JOIN accounts a ON a.account_id=r.account_id
+WHERE a.user_id=($1) AND a.deleted_at IS NULL AND NOT r.paused AND r.next_date <= ($2)
+  AND (r.end_date IS NULL OR r.next_date <= r.end_date)
+ORDER BY r.next_date, r.recurring_id
+        "#,
+    )
+    .bind(user_id)
+    .bind(until)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(recurring)
+}
+
+async fn recurring_get_due(
+    pool: &PgPool,
+    today: &NaiveDate,
+) -> Result<Vec<RecurringTransaction>, sqlx::Error> {
+    let recurring: Vec<RecurringTransaction> = sqlx::query_as(
+        r#"
+SELECT r.*, a.account_name
+FROM recurring_transactions r
+JOIN accounts a ON a.account_id=r.account_id
+JOIN users u ON u.user_id=a.user_id
+WHERE NOT r.paused AND r.next_date <= ($1)
+  AND a.deleted_at IS NULL AND u.deleted_at IS NULL
+  AND (r.end_date IS NULL OR r.next_date <= r.end_date)
+        "#,
+    )
+    .bind(today)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(recurring)
+}
+
+/*****************************************************************************/
+/*                                Audit APIs                                 */
+/*****************************************************************************/
+
+async fn audit_snapshot(
+    executor: impl PgExecutor<'_>,
+    entity: AuditEntity,
+    entity_id: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    let snapshot: Option<(String,)> = sqlx::query_as(entity.snapshot_query())
+        .bind(entity_id)
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(snapshot.map(|s| s.0))
+}
+
+// Records a change given the state before it, reading the state after it from
+// the database. A missing before state is a creation, a missing after state a
+// deletion.
+async fn audit_record(
+    conn: &mut PgConnection,
+    entity: AuditEntity,
+    entity_id: i64,
+    actor: &str,
+    before: Option<String>,
+) -> Result<(), sqlx::Error> {
+    let action = match (&before, audit_snapshot(&mut *conn, entity, entity_id).await?) {
+        (None, _) => AuditAction::Create,
+        (Some(_), None) => AuditAction::Delete,
+        (Some(_), Some(_)) => AuditAction::Update,
+    };
+    audit_record_action(conn, entity, entity_id, actor, action, before).await
+}
+
+// Records a change whose action cannot be told from the before and after
+// states, such as moving a row to the trash
+async fn audit_record_action(
+    conn: &mut PgConnection,
+    entity: AuditEntity,
+    entity_id: i64,
+    actor: &str,
+    action: AuditAction,
+    before: Option<String>,
+) -> Result<(), sqlx::Error> {
+    let after = audit_snapshot(&mut *conn, entity, entity_id).await?;
+    sqlx::query(
+        r#"
+INSERT INTO audit_log (entity_type, entity_id, action, actor, before_value, after_value)
+VALUES ($1, $2, $3, $4, ($5)::jsonb, ($6)::jsonb)
+        "#,
+    )
+    .bind(entity.to_string())
+    .bind(entity_id)
+    .bind(action.to_string())
+    .bind(actor)
+    .bind(before)
+    .bind(after)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn audit_get_all_for_entity(
+    pool: &PgPool,
+    entity: AuditEntity,
+    entity_id: i64,
+) -> Result<Vec<AuditRow>, sqlx::Error> {
+    let rows: Vec<AuditRow> = sqlx::query_as(
+        r#"
+SELECT audit_id, entity_type, entity_id, action, actor,
+       before_value::text AS before_value, after_value::text AS after_value, changed_at
+FROM audit_log
+WHERE entity_type=($1) AND entity_id=($2)
+ORDER BY audit_id
+        "#,
+    )
+    .bind(entity.to_string())
+    .bind(entity_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/*****************************************************************************/
+/*                                Trash APIs                                 */
+/*****************************************************************************/
+
+// Table and key column of the entities that can be moved to the trash
+fn trash_table(entity: AuditEntity) -> Result<(&'static str, &'static str), sqlx::Error> {
+    match entity {
+        AuditEntity::User => Ok(("users", "user_id")),
+        AuditEntity::Account => Ok(("accounts", "account_id")),
+        AuditEntity::Transaction => Ok(("transactions", "transaction_id")),
+        _ => Err(sqlx::Error::InvalidArgument(format!(
+            "{} cannot be moved to the trash",
+            entity
+        ))),
+    }
+}
+
+async fn trash_get_all_for_user(
+    pool: &PgPool,
+    user_id: i64,
+) -> Result<Vec<TrashItem>, sqlx::Error> {
+    let items: Vec<TrashItem> = sqlx::query_as(
+        r#"
+SELECT trash.*, trash.deleted_at + make_interval(days => ($2)) AS purge_at
+FROM (
+    SELECT 'User' AS entity_type, u.user_id AS entity_id, u.username AS description,
+           u.deleted_at
+    FROM users u
+    WHERE u.user_id=($1) AND u.deleted_at IS NOT NULL
+    UNION ALL
+    SELECT 'Account', a.account_id, COALESCE(a.account_name, ''), a.deleted_at
+    FROM accounts a
+    WHERE a.user_id=($1) AND a.deleted_at IS NOT NULL
+    UNION ALL
+    SELECT 'Transaction', t.transaction_id,
+           t.transaction_date::text || ' ' || COALESCE(t.transaction_memo, '')
+               || ' (' || COALESCE(a.account_name, '') || ')',
+           t.deleted_at
+    FROM transactions t
+    JOIN accounts a ON a.account_id=t.account_id
+    WHERE a.user_id=($1) AND t.deleted_at IS NOT NULL
+) AS trash
+ORDER BY trash.deleted_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .bind(TRASH_RETENTION_DAYS)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
+
+async fn trash_restore(
+    executor: impl PgExecutor<'_>,
+    entity: AuditEntity,
+    entity_id: i64,
+) -> Result<u64, sqlx::Error> {
+    let (table, key) = trash_table(entity)?;
+    let rows = sqlx::query(&format!(
+        "UPDATE {} SET deleted_at=NULL WHERE {}=($1) AND deleted_at IS NOT NULL",
+        table, key
+    ))
+    .bind(entity_id)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+async fn trash_get_expired(pool: &PgPool, entity: AuditEntity) -> Result<Vec<i64>, sqlx::Error> {
+    let (table, key) = trash_table(entity)?;
+    let ids: Vec<(i64,)> = sqlx::query_as(&format!(
+        "SELECT {} FROM {} WHERE deleted_at < NOW() - make_interval(days => ($1)) ORDER BY {}",
+        key, table, key
+    ))
+    .bind(TRASH_RETENTION_DAYS)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ids.into_iter().map(|id| id.0).collect())
+}
+
+// The user or account in the trash that hides the entity, if any
+async fn trash_get_trashed_parent(
+    executor: impl PgExecutor<'_>,
+    entity: AuditEntity,
+    entity_id: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    let query = match entity {
+        AuditEntity::Account => {
+            r#"
+SELECT 'User'
+FROM accounts a
+JOIN users u ON u.user_id=a.user_id
+WHERE a.account_id=($1) AND u.deleted_at IS NOT NULL
+            "#
+        }
+        AuditEntity::Transaction => {
+            r#"
+SELECT CASE WHEN u.deleted_at IS NOT NULL THEN 'User' ELSE 'Account' END
+FROM transactions t
+JOIN accounts a ON a.account_id=t.account_id
+JOIN users u ON u.user_id=a.user_id
+WHERE t.transaction_id=($1) AND (a.deleted_at IS NOT NULL OR u.deleted_at IS NOT NULL)
+            "#
+        }
+        _ => return Ok(None),
+    };
+    let parent: Option<(String,)> = sqlx::query_as(query)
+        .bind(entity_id)
+        .fetch_optional(executor)
+        .await?;
+
+    Ok(parent.map(|p| p.0))
+}
+
+// The audited rows the foreign keys delete along with an entity, children
+// before their parents
+async fn trash_get_cascaded(
+    executor: impl PgExecutor<'_>,
+    entity: AuditEntity,
+    entity_id: i64,
+) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+WITH accts AS (
+    SELECT account_id
+    FROM accounts
+    WHERE (($2)='Account' AND account_id=($1)) OR (($2)='User' AND user_id=($1))
+)
+SELECT 'Attachment', f.attachment_id
+FROM attachments f
+JOIN transactions t ON t.transaction_id=f.transaction_id
+WHERE (($2)='Transaction' AND t.transaction_id=($1)) OR t.account_id IN (SELECT account_id FROM accts)
+UNION ALL
+SELECT 'Transaction', transaction_id
+FROM transactions
+WHERE account_id IN (SELECT account_id FROM accts)
+UNION ALL
+SELECT 'Recurring', recurring_id
+FROM recurring_transactions
+WHERE account_id IN (SELECT account_id FROM accts)
+UNION ALL
+SELECT 'Budget', budget_id
+FROM budgets
+WHERE account_id IN (SELECT account_id FROM accts) OR (($2)='User' AND user_id=($1))
+UNION ALL
+SELECT 'Category', category_id
+FROM categories
+WHERE ($2)='User' AND user_id=($1)
+UNION ALL
+SELECT 'Account', account_id
+FROM accts
+WHERE ($2)='User'
+        "#,
+    )
+    .bind(entity_id)
+    .bind(entity.to_string())
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows)
+}
+
+async fn trash_purge(executor: impl PgExecutor<'_>, entity: AuditEntity, entity_id: i64) -> Result<u64, sqlx::Error> {
+    let (table, key) = trash_table(entity)?;
+    let rows = sqlx::query(&format!(
+        "DELETE FROM {} WHERE {}=($1) AND deleted_at IS NOT NULL",
+        table, key
+    ))
+    .bind(entity_id)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+/*****************************************************************************/
+/*                          Balance Snapshot APIs                            */
+/*****************************************************************************/
+
+// Record the balance of every account as of the given date, overwriting any
+// snapshot already taken for that day
+async fn balance_snapshot_create_all(pool: &PgPool, date: &NaiveDate) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+INSERT INTO balance_snapshots (account_id, snapshot_date, balance)
+SELECT a.account_id, ($1),
+       COALESCE((
+           SELECT SUM(t.amount)
+           FROM live_transactions t
+           WHERE t.account_id=a.account_id AND t.transaction_date <= ($1)
+       ), 0)
+FROM accounts a
+JOIN users u ON u.user_id=a.user_id
+WHERE a.deleted_at IS NULL AND u.deleted_at IS NULL
+ON CONFLICT (account_id, snapshot_date) DO UPDATE SET balance=EXCLUDED.balance
+        "#,
+    )
+    .bind(date)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
+}
+
+// Drop the snapshots a change to the transaction makes stale, those of its
+// account from its date on, so the series falls back to the transaction
+// history until the scheduler takes them again
+async fn balance_snapshot_delete_for_transaction(
+    executor: impl PgExecutor<'_>,
+    transaction_id: i64,
+) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+DELETE FROM balance_snapshots s
+USING transactions t
+WHERE t.transaction_id=($1) AND s.account_id=t.account_id AND s.snapshot_date >= t.transaction_date
+        "#,
+    )
+    .bind(transaction_id)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(rows)
 }
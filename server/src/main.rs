@@ -1,18 +1,22 @@
 mod db;
+mod scheduler;
 mod server;
 use anyhow::{Context, Result};
 use sqlx::PgPool;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let pool = PgPool::connect(&db::PG_CONNECTION_STR)
+    let pool = PgPool::connect(db::PG_CONNECTION_STR)
         .await
         .context("Failed to connect to the database")?;
 
+    // start the background jobs
+    tokio::spawn(scheduler::run_scheduler(pool.clone()));
+
     // start the server
     if let Err(e) = server::run_server(pool.clone()).await {
         eprintln!("Error while running server: {}", e);
-        return Err(anyhow::anyhow!("Server failed to start").into());
+        return Err(anyhow::anyhow!("Server failed to start"));
     }
 
     Ok(())
@@ -0,0 +1,36 @@
+use crate::db;
+use chrono::Local;
+use sqlx::postgres::PgPool;
+use std::time::Duration;
+
+// how often the background jobs wake up
+const SCHEDULER_PERIOD_SECS: u64 = 60 * 60;
+
+// actor recorded in the audit log for changes made by the background jobs
+const SCHEDULER_ACTOR: &str = "scheduler";
+
+// Background jobs that run for the lifetime of the server. Every job is
+// idempotent, so running it more than once a day is harmless.
+pub async fn run_scheduler(db_pool: PgPool) {
+    let mut interval = tokio::time::interval(Duration::from_secs(SCHEDULER_PERIOD_SECS));
+    loop {
+        interval.tick().await;
+        let today = Local::now().date_naive();
+
+        // create due recurring transactions first so the snapshots include them
+        match db::materialize_recurring_transactions(&db_pool, &today, SCHEDULER_ACTOR).await {
+            Ok(n) => println!("::[SERVER LOG] Scheduler: created {} recurring transactions", n),
+            Err(e) => eprintln!("::[SERVER LOG] Scheduler: recurring transactions failed: {}", e),
+        }
+
+        match db::take_balance_snapshots(&db_pool, &today).await {
+            Ok(n) => println!("::[SERVER LOG] Scheduler: recorded {} balance snapshots", n),
+            Err(e) => eprintln!("::[SERVER LOG] Scheduler: balance snapshots failed: {}", e),
+        }
+
+        match db::purge_trash(&db_pool, SCHEDULER_ACTOR).await {
+            Ok(n) => println!("::[SERVER LOG] Scheduler: purged {} entities from the trash", n),
+            Err(e) => eprintln!("::[SERVER LOG] Scheduler: trash purge failed: {}", e),
+        }
+    }
+}
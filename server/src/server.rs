@@ -1,5 +1,8 @@
 use crate::db;
-use actix_web::{web, App, HttpResponse, HttpServer, Responder};
+use actix_web::http::header::{
+    Charset, ContentDisposition, DispositionParam, DispositionType, ExtendedValue,
+};
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Responder};
 use chrono::NaiveDate;
 use serde::Deserialize;
 use sqlx::postgres::PgPool;
@@ -27,6 +30,111 @@ pub struct TransactionInfo {
     pub amount: Option<f64>,
     pub transaction_memo: Option<String>,
     pub account_id: i64,
+    pub tags: Option<String>,
+    pub tag_match: Option<db::TagMatch>,
+    pub splits: Option<String>, // JSON list of split lines
+}
+
+#[derive(Deserialize)]
+pub struct TransactionData {
+    pub transaction_id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct UploadInfo {
+    pub file_name: String,
+}
+
+#[derive(Deserialize)]
+pub struct TagInfo {
+    pub transaction_id: i64,
+    pub tag_name: String,
+}
+
+#[derive(Deserialize)]
+pub struct CategoryInfo {
+    pub category_id: Option<i64>,
+    pub username: String,
+    pub category_name: String,
+    pub parent_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct BudgetInfo {
+    pub budget_id: Option<i64>,
+    pub username: String,
+    pub category_name: String,
+    pub account_id: Option<i64>,
+    pub budget_amount: f64,
+}
+
+#[derive(Deserialize)]
+pub struct BudgetQuery {
+    pub username: String,
+    pub month: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct RecurringInfo {
+    pub recurring_id: Option<i64>,
+    pub account_id: i64,
+    pub transaction_type: db::TransactionType,
+    pub category: String,
+    pub amount: f64,
+    pub transaction_memo: String,
+    pub frequency: db::Frequency,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct PauseInfo {
+    pub recurring_id: i64,
+    pub paused: bool,
+}
+
+#[derive(Deserialize)]
+pub struct ReportInfo {
+    pub account_id: i64,
+    pub tags: Option<String>,
+    pub tag_match: Option<db::TagMatch>,
+}
+
+#[derive(Deserialize)]
+pub struct ChartInfo {
+    pub account_id: i64,
+    pub transaction_type: Option<db::TransactionType>,
+    pub category: Option<String>,
+    pub tags: Option<String>,
+    pub tag_match: Option<db::TagMatch>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryInfo {
+    pub username: String,
+    pub granularity: db::Granularity,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct AuditInfo {
+    pub entity_type: db::AuditEntity,
+    pub entity_id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct RestoreInfo {
+    pub entity_type: db::AuditEntity,
+    pub entity_id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct BalanceInfo {
+    pub account_id: i64,
+    pub date: Option<NaiveDate>,
 }
 
 pub async fn run_server(db_pool: PgPool) -> std::io::Result<()> {
@@ -47,6 +155,8 @@ pub async fn run_server(db_pool: PgPool) -> std::io::Result<()> {
                 "/create_or_update_transaction",
                 web::post().to(create_or_update_transaction),
             )
+            .route("/query_user_summary", web::post().to(query_user_summary))
+            .route("/query_dashboard", web::post().to(query_dashboard))
             .route("/delete_user", web::post().to(delete_user))
             .route("/delete_account/{account_id}", web::get().to(delete_account))
             .route(
@@ -54,6 +164,65 @@ pub async fn run_server(db_pool: PgPool) -> std::io::Result<()> {
                 web::get().to(delete_transaction),
             )
             .route("/query_account", web::post().to(query_account))
+            .route("/add_transaction_tag", web::post().to(add_transaction_tag))
+            .route(
+                "/remove_transaction_tag",
+                web::post().to(remove_transaction_tag),
+            )
+            .route("/query_attachments", web::post().to(query_attachments))
+            .service(
+                // the file is sent as the raw request body
+                web::resource("/upload_attachment/{transaction_id}")
+                    .app_data(web::PayloadConfig::new(db::MAX_ATTACHMENT_SIZE))
+                    .route(web::post().to(upload_attachment)),
+            )
+            .route(
+                "/download_attachment/{attachment_id}",
+                web::get().to(download_attachment),
+            )
+            .route(
+                "/delete_attachment/{attachment_id}",
+                web::get().to(delete_attachment),
+            )
+            .route("/query_categories", web::post().to(query_categories))
+            .route("/query_category_usage", web::post().to(query_category_usage))
+            .route(
+                "/query_category_report",
+                web::post().to(query_category_report),
+            )
+            .route(
+                "/create_or_update_category",
+                web::post().to(create_or_update_category),
+            )
+            .route(
+                "/delete_category/{category_id}",
+                web::get().to(delete_category),
+            )
+            .route("/query_budgets", web::post().to(query_budgets))
+            .route(
+                "/create_or_update_budget",
+                web::post().to(create_or_update_budget),
+            )
+            .route("/delete_budget/{budget_id}", web::get().to(delete_budget))
+            .route("/query_recurring", web::post().to(query_recurring))
+            .route(
+                "/create_or_update_recurring",
+                web::post().to(create_or_update_recurring),
+            )
+            .route("/pause_recurring", web::post().to(pause_recurring))
+            .route(
+                "/delete_recurring/{recurring_id}",
+                web::get().to(delete_recurring),
+            )
+            .route("/query_account_balance", web::post().to(query_account_balance))
+            .route("/query_audit_history", web::post().to(query_audit_history))
+            .route(
+                "/query_net_worth_history",
+                web::post().to(query_net_worth_history),
+            )
+            .route("/query_spending_charts", web::post().to(query_spending_charts))
+            .route("/query_trash", web::post().to(query_trash))
+            .route("/restore_from_trash", web::post().to(restore_from_trash))
     })
     .bind("localhost:8080")?
     .run()
@@ -63,39 +232,121 @@ pub async fn run_server(db_pool: PgPool) -> std::io::Result<()> {
 /*****************************************************************************/
 /* API handlers */
 
+// The user making a change, as reported by the client in the X-Actor header
+fn actor(req: &HttpRequest) -> String {
+    req.headers()
+        .get("X-Actor")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+// Missing rows and rejected input are the caller's fault, anything else is ours
+fn error_response(e: sqlx::Error) -> HttpResponse {
+    let body = format!("Error: {}", e);
+    match e {
+        sqlx::Error::RowNotFound => HttpResponse::NotFound().json(body),
+        sqlx::Error::InvalidArgument(_) => HttpResponse::BadRequest().json(body),
+        _ => HttpResponse::InternalServerError().json(body),
+    }
+}
+
+// A form field the request cannot do without, rejected as bad input when missing
+fn required<'a, T>(value: &'a Option<T>, name: &str) -> Result<&'a T, sqlx::Error> {
+    value
+        .as_ref()
+        .ok_or_else(|| sqlx::Error::InvalidArgument(format!("missing {}", name)))
+}
+
+// Offers the stored file name as UTF-8 (RFC 6266), with a plain ASCII copy for
+// clients that do not read filename*
+fn attachment_disposition(file_name: &str) -> ContentDisposition {
+    let ascii_name: String = file_name
+        .chars()
+        .map(|c| if c == ' ' || (c.is_ascii_graphic() && c != '"' && c != '\\') { c } else { '_' })
+        .collect();
+    ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters: vec![
+            DispositionParam::Filename(ascii_name),
+            DispositionParam::FilenameExt(ExtendedValue {
+                charset: Charset::Ext("UTF-8".to_string()),
+                language_tag: None,
+                value: file_name.as_bytes().to_vec(),
+            }),
+        ],
+    }
+}
+
 // check whether server is up
 async fn greet() -> impl Responder {
     HttpResponse::Ok().body("Server is up!")
 }
 
 async fn query_or_create_user(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
     user_data: web::Form<UserData>,
 ) -> impl Responder {
     println!("::[SERVER LOG] Request: query_or_create_user...");
     let username = &user_data.username;
-    match db::query_or_create_user(&pool, username).await {
+    match db::query_or_create_user(&pool, username, &actor(&req)).await {
         Ok(result) => {
             println!("::[SERVER LOG] Done");
             HttpResponse::Ok().json(result)
         },
-        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+        Err(e) => error_response(e),
     }
 }
 
-async fn delete_user(pool: web::Data<PgPool>, user_data: web::Form<UserData>) -> impl Responder {
+async fn query_user_summary(
+    pool: web::Data<PgPool>,
+    user_data: web::Form<UserData>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_user_summary...");
+    let username = &user_data.username;
+    match db::query_user_summary(&pool, username).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        },
+        Err(e) => error_response(e),
+    }
+}
+
+async fn query_dashboard(
+    pool: web::Data<PgPool>,
+    user_data: web::Form<UserData>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_dashboard...");
+    let username = &user_data.username;
+    match db::query_dashboard(&pool, username).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        },
+        Err(e) => error_response(e),
+    }
+}
+
+async fn delete_user(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    user_data: web::Form<UserData>,
+) -> impl Responder {
     println!("::[SERVER LOG] Request: delete_user...");
     let username = &user_data.username;
-    match db::delete_single_user(&pool, username).await {
+    match db::delete_single_user(&pool, username, &actor(&req)).await {
         Ok(_) => {
             println!("::[SERVER LOG] Done");
             HttpResponse::Ok().finish()
         },
-        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+        Err(e) => error_response(e),
     }
 }
 
 async fn create_or_update_account(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
     info: web::Form<AccountInfo>,
 ) -> impl Responder {
@@ -112,6 +363,7 @@ async fn create_or_update_account(
         account_name,
         account_type,
         account_limit,
+        &actor(&req),
     )
     .await
     {
@@ -119,33 +371,51 @@ async fn create_or_update_account(
             println!("::[SERVER LOG] Done");
             HttpResponse::Ok().json(result)
         },
-        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+        Err(e) => error_response(e),
     }
 }
 
-async fn delete_account(pool: web::Data<PgPool>, account_id: web::Path<i64>) -> impl Responder {
+async fn delete_account(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    account_id: web::Path<i64>,
+) -> impl Responder {
     println!("::[SERVER LOG] Request: delete_account...");
-    match db::delete_single_account(&pool, account_id.into_inner()).await {
+    match db::delete_single_account(&pool, account_id.into_inner(), &actor(&req)).await {
         Ok(_) => {
             println!("::[SERVER LOG] Done");
             HttpResponse::Ok().finish() // return status code: 200 OK
         },
-        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+        Err(e) => error_response(e),
     }
 }
 
 async fn create_or_update_transaction(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
     info: web::Form<TransactionInfo>,
 ) -> impl Responder {
     println!("::[SERVER LOG] Request: create_or_update_transaction");
 
-    let transaction_date = &info.transaction_date.unwrap();
-    let transaction_type = &info.transaction_type.as_ref().unwrap();
-    let category = &info.category.as_ref().unwrap();
-    let amount = info.amount.unwrap();
-    let transaction_memo = &info.transaction_memo.as_ref().unwrap();
+    let fields = || -> Result<_, sqlx::Error> {
+        Ok((
+            required(&info.transaction_date, "transaction_date")?,
+            required(&info.transaction_type, "transaction_type")?,
+            required(&info.category, "category")?,
+            *required(&info.amount, "amount")?,
+            required(&info.transaction_memo, "transaction_memo")?,
+        ))
+    };
+    let (transaction_date, transaction_type, category, amount, transaction_memo) = match fields() {
+        Ok(fields) => fields,
+        Err(e) => return error_response(e),
+    };
     let account_id = info.account_id;
+    let tags = info.tags.as_deref().map(db::parse_tags);
+    let splits: Option<Vec<db::SplitLine>> = match info.splits.as_deref().map(serde_json::from_str).transpose() {
+        Ok(splits) => splits,
+        Err(e) => return HttpResponse::BadRequest().json(format!("Error: {}", e)),
+    };
 
     match db::create_or_update_transaction(
         &pool,
@@ -156,6 +426,9 @@ async fn create_or_update_transaction(
         amount,
         transaction_memo,
         account_id,
+        &tags,
+        &splits,
+        &actor(&req),
     )
     .await
     {
@@ -163,21 +436,22 @@ async fn create_or_update_transaction(
             println!("::[SERVER LOG] Done");
             HttpResponse::Ok().json(result)
         },
-        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+        Err(e) => error_response(e),
     }
 }
 
 async fn delete_transaction(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
     transaction_id: web::Path<i64>,
 ) -> impl Responder {
     println!("::[SERVER LOG] Request: delete_transaction");
-    match db::delete_single_transaction(&pool, transaction_id.into_inner()).await {
+    match db::delete_single_transaction(&pool, transaction_id.into_inner(), &actor(&req)).await {
         Ok(_) => {
             println!("::[SERVER LOG] Done");
             HttpResponse::Ok().finish()
         },
-        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+        Err(e) => error_response(e),
     }
 }
 
@@ -188,17 +462,424 @@ async fn query_account(
     println!("::[SERVER LOG] Request: query_account");
 
     let account_id = info.account_id;
+    let tags = info.tags.as_deref().map(db::parse_tags).unwrap_or_default();
     match db::query_account_transactions(
         &pool,
         account_id,
         &info.transaction_type,
         &info.category,
+        &tags,
+        &info.tag_match.unwrap_or_default(),
     ).await {
         Ok(result) => {
             println!("::[SERVER LOG] Done");
             HttpResponse::Ok().json(result)
         }
-        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e))
+        Err(e) => error_response(e)
+    }
+}
+
+async fn add_transaction_tag(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    info: web::Form<TagInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: add_transaction_tag");
+    match db::add_transaction_tag(&pool, info.transaction_id, &info.tag_name, &actor(&req)).await {
+        Ok(_) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().finish()
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn remove_transaction_tag(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    info: web::Form<TagInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: remove_transaction_tag");
+    match db::remove_transaction_tag(&pool, info.transaction_id, &info.tag_name, &actor(&req)).await {
+        Ok(_) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().finish()
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn query_attachments(
+    pool: web::Data<PgPool>,
+    info: web::Form<TransactionData>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_attachments");
+    match db::query_attachments(&pool, info.transaction_id).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn upload_attachment(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    transaction_id: web::Path<i64>,
+    info: web::Query<UploadInfo>,
+    body: web::Bytes,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: upload_attachment");
+    match db::create_attachment(&pool, transaction_id.into_inner(), &info.file_name, &body, &actor(&req)).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn download_attachment(
+    pool: web::Data<PgPool>,
+    attachment_id: web::Path<i64>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: download_attachment");
+    match db::query_attachment_content(&pool, attachment_id.into_inner()).await {
+        Ok(attachment) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok()
+                .content_type(attachment.content_type)
+                .insert_header(attachment_disposition(&attachment.file_name))
+                .body(attachment.content)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn delete_attachment(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    attachment_id: web::Path<i64>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: delete_attachment");
+    match db::delete_single_attachment(&pool, attachment_id.into_inner(), &actor(&req)).await {
+        Ok(_) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().finish()
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn query_categories(
+    pool: web::Data<PgPool>,
+    user_data: web::Form<UserData>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_categories");
+    match db::query_categories(&pool, &user_data.username).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn query_category_usage(
+    pool: web::Data<PgPool>,
+    user_data: web::Form<UserData>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_category_usage");
+    match db::query_category_usage(&pool, &user_data.username).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn query_category_report(
+    pool: web::Data<PgPool>,
+    info: web::Form<ReportInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_category_report");
+    let tags = info.tags.as_deref().map(db::parse_tags).unwrap_or_default();
+    let tag_match = info.tag_match.unwrap_or_default();
+    match db::query_category_report(&pool, info.account_id, &tags, &tag_match).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn query_spending_charts(
+    pool: web::Data<PgPool>,
+    info: web::Form<ChartInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_spending_charts");
+    let tags = info.tags.as_deref().map(db::parse_tags).unwrap_or_default();
+    match db::query_spending_charts(
+        &pool,
+        info.account_id,
+        &info.transaction_type,
+        &info.category,
+        &tags,
+        &info.tag_match.unwrap_or_default(),
+        &info.start_date,
+        &info.end_date,
+    ).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn create_or_update_category(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    info: web::Form<CategoryInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: create_or_update_category");
+    match db::create_or_update_category(
+        &pool,
+        info.category_id,
+        &info.username,
+        &info.category_name,
+        info.parent_id,
+        &actor(&req),
+    )
+    .await
+    {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        },
+        Err(e) => error_response(e),
+    }
+}
+
+async fn delete_category(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    category_id: web::Path<i64>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: delete_category");
+    match db::delete_single_category(&pool, category_id.into_inner(), &actor(&req)).await {
+        Ok(_) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().finish()
+        },
+        Err(e) => error_response(e),
+    }
+}
+
+async fn query_budgets(
+    pool: web::Data<PgPool>,
+    info: web::Form<BudgetQuery>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_budgets");
+    match db::query_budgets(&pool, &info.username, &info.month).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn create_or_update_budget(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    info: web::Form<BudgetInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: create_or_update_budget");
+    match db::create_or_update_budget(
+        &pool,
+        info.budget_id,
+        &info.username,
+        &info.category_name,
+        info.account_id,
+        info.budget_amount,
+        &actor(&req),
+    )
+    .await
+    {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        },
+        Err(e) => error_response(e),
+    }
+}
+
+async fn delete_budget(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    budget_id: web::Path<i64>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: delete_budget");
+    match db::delete_single_budget(&pool, budget_id.into_inner(), &actor(&req)).await {
+        Ok(_) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().finish()
+        },
+        Err(e) => error_response(e),
+    }
+}
+
+async fn query_recurring(
+    pool: web::Data<PgPool>,
+    user_data: web::Form<UserData>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_recurring");
+    match db::query_recurring(&pool, &user_data.username).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn create_or_update_recurring(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    info: web::Form<RecurringInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: create_or_update_recurring");
+    match db::create_or_update_recurring(
+        &pool,
+        info.recurring_id,
+        info.account_id,
+        &info.transaction_type,
+        &info.category,
+        info.amount,
+        &info.transaction_memo,
+        &info.frequency,
+        &info.start_date,
+        &info.end_date,
+        &actor(&req),
+    )
+    .await
+    {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        },
+        Err(e) => error_response(e),
+    }
+}
+
+async fn pause_recurring(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    info: web::Form<PauseInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: pause_recurring");
+    match db::set_recurring_paused(&pool, info.recurring_id, info.paused, &actor(&req)).await {
+        Ok(_) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().finish()
+        },
+        Err(e) => error_response(e),
+    }
+}
+
+async fn delete_recurring(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    recurring_id: web::Path<i64>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: delete_recurring");
+    match db::delete_single_recurring(&pool, recurring_id.into_inner(), &actor(&req)).await {
+        Ok(_) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().finish()
+        },
+        Err(e) => error_response(e),
+    }
+}
+
+async fn query_account_balance(
+    pool: web::Data<PgPool>,
+    info: web::Form<BalanceInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_account_balance");
+    match db::query_account_balance_on_date(&pool, info.account_id, &info.date).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn query_net_worth_history(
+    pool: web::Data<PgPool>,
+    info: web::Form<HistoryInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_net_worth_history");
+    match db::query_net_worth_history(
+        &pool,
+        &info.username,
+        &info.granularity,
+        &info.start_date,
+        &info.end_date,
+    ).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn query_audit_history(
+    pool: web::Data<PgPool>,
+    info: web::Form<AuditInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_audit_history");
+    match db::query_audit_history(&pool, info.entity_type, info.entity_id).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn query_trash(
+    pool: web::Data<PgPool>,
+    user_data: web::Form<UserData>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: query_trash");
+    match db::query_trash(&pool, &user_data.username).await {
+        Ok(result) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => error_response(e)
+    }
+}
+
+async fn restore_from_trash(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    info: web::Form<RestoreInfo>,
+) -> impl Responder {
+    println!("::[SERVER LOG] Request: restore_from_trash");
+    match db::restore_from_trash(&pool, info.entity_type, info.entity_id, &actor(&req)).await {
+        Ok(_) => {
+            println!("::[SERVER LOG] Done");
+            HttpResponse::Ok().finish()
+        }
+        Err(e) => error_response(e)
     }
 }
 